@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use ::serde::{Deserialize, Serialize};
+use ::tracing::error;
+
+use crate::player::PasswordHash;
+use crate::utils::{TomlFileError, load_toml, save_toml, unix_now};
+
+/// Player account: a name reserved by its password.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    /// Name as it was claimed.
+    pub name: String,
+    pub pwd: PasswordHash,
+    /// Unix time of the first login.
+    pub first_seen: u64,
+    /// Unix time of the last login.
+    pub last_seen: u64,
+}
+
+/// Result of `AccountStore::login`.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Login {
+    /// The name is not reserved and no password was given.
+    Guest,
+    /// The name has been reserved by this login.
+    Claimed,
+    /// The password matches the account.
+    Verified,
+    /// The name is reserved by another password.
+    WrongPassword,
+}
+
+/// Password of a login checked against an account, see `Checked::new`.
+/// It is made apart from the store because hashing is slow.
+#[derive(Debug)]
+pub enum Checked {
+    /// Empty password for a free name.
+    Guest,
+    /// Non-empty password for a free name, with its hash to claim the name.
+    Free(PasswordHash),
+    /// The password matches, with a new hash if the stored one is outdated.
+    Verified(Option<PasswordHash>),
+    WrongPassword,
+}
+
+impl Checked {
+    /// Checks `pwd` (without null-terminator) against the `account` hash,
+    /// `None` for a free name.
+    pub fn new(account: Option<&PasswordHash>, pwd: &[u8]) -> Self {
+        match account {
+            Some(hash) if !hash.verify(pwd) => Self::WrongPassword,
+            Some(hash) => Self::Verified(hash.needs_rehash().then(|| PasswordHash::new(pwd))),
+            None if pwd.is_empty() => Self::Guest,
+            None => Self::Free(PasswordHash::new(pwd)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AccountsFile {
+    /// Accounts by lowercase names.
+    accounts: BTreeMap<String, Account>,
+}
+
+/// Accounts saved in a TOML file. The file is rewritten when a name is claimed
+/// or a password is rehashed, login times are saved by `AccountStore::flush`.
+#[derive(Debug, Default)]
+pub struct AccountStore {
+    /// `None` for the in-memory store.
+    path: Option<PathBuf>,
+    file: AccountsFile,
+    /// `true` if login times are changed since the last save.
+    dirty: bool,
+    /// Lowercase names which cannot be claimed, see `AccountStore::reserve`.
+    reserved: Vec<String>,
+}
+
+impl AccountStore {
+    /// Opens the store at `path`, the file is created on the first change.
+    pub fn open(path: &Path) -> Result<Self, TomlFileError> {
+        Ok(Self {
+            path: Some(path.to_owned()),
+            file: load_toml(path)?,
+            dirty: false,
+            reserved: vec![],
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.file.accounts.len()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.file.accounts.get(&name.to_lowercase())
+    }
+
+    /// Returns the password hash of the `name` account for `Checked::new`.
+    pub fn pwd_of(&self, name: &str) -> Option<PasswordHash> {
+        self.get(name).map(|account| account.pwd.clone())
+    }
+
+    /// Forbids to claim `names` (admins) by a login, their accounts
+    /// are added by `AccountStore::add` only.
+    pub fn reserve(&mut self, names: &[String]) {
+        self.reserved = names.iter().map(|name| name.to_lowercase()).collect();
+    }
+
+    /// Adds the `name` account with the password hash `pwd`,
+    /// returns `false` if the name is already claimed.
+    pub fn add(&mut self, name: &str, pwd: PasswordHash) -> Result<bool, TomlFileError> {
+        if self.get(name).is_some() {
+            return Ok(false);
+        }
+
+        let now = unix_now();
+        let account = Account {
+            name: name.to_string(),
+            pwd,
+            first_seen: now,
+            last_seen: now,
+        };
+        self.file.accounts.insert(name.to_lowercase(), account);
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Logs in the `name` account by the `checked` password.
+    /// The first login with a non-empty password claims the name,
+    /// unless it is reserved by `AccountStore::reserve`.
+    pub fn login(&mut self, name: &str, checked: Checked) -> Result<Login, TomlFileError> {
+        let now = unix_now();
+        let key = name.to_lowercase();
+        let reserved = self.reserved.contains(&key);
+
+        let login = match (self.file.accounts.get_mut(&key), checked) {
+            (Some(account), Checked::Verified(rehash)) => {
+                account.last_seen = now;
+                let Some(pwd) = rehash else {
+                    self.dirty = true;
+                    return Ok(Login::Verified);
+                };
+                account.pwd = pwd;
+                Login::Verified
+            }
+            (None, _) if reserved => return Ok(Login::WrongPassword),
+            (None, Checked::Guest) => return Ok(Login::Guest),
+            (None, Checked::Free(pwd)) => {
+                let account = Account {
+                    name: name.to_string(),
+                    pwd,
+                    first_seen: now,
+                    last_seen: now,
+                };
+                self.file.accounts.insert(key, account);
+                Login::Claimed
+            }
+            // also if the name has been claimed while the password was checked
+            _ => return Ok(Login::WrongPassword),
+        };
+
+        self.save()?;
+        Ok(login)
+    }
+
+    /// Saves login times changed since the last save.
+    pub fn flush(&mut self) -> Result<(), TomlFileError> {
+        match self.dirty {
+            true => self.save(),
+            false => Ok(()),
+        }
+    }
+
+    fn save(&mut self) -> Result<(), TomlFileError> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        self.dirty = false;
+
+        save_toml(path, &self.file)
+    }
+}
+
+impl Drop for AccountStore {
+    /// Login times are not lost at shutdown.
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            error!("{}", err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn try_login(store: &mut AccountStore, name: &str, pwd: &[u8]) -> Login {
+        let checked = Checked::new(store.pwd_of(name).as_ref(), pwd);
+        store.login(name, checked).unwrap()
+    }
+
+    #[test]
+    fn login() {
+        let mut store = AccountStore::default();
+
+        assert_eq!(Login::Guest, try_login(&mut store, "Guest", b""));
+        assert!(store.get("guest").is_none());
+
+        assert_eq!(Login::Claimed, try_login(&mut store, "Fenex", b"pwd"));
+        assert_eq!(Login::Verified, try_login(&mut store, "fenex", b"pwd"));
+        assert_eq!(Login::WrongPassword, try_login(&mut store, "FENEX", b""));
+        assert_eq!(Login::WrongPassword, try_login(&mut store, "Fenex", b"pw"));
+
+        let account = store.get("fenex").unwrap();
+        assert_eq!("Fenex", account.name);
+        assert!(account.first_seen <= account.last_seen);
+    }
+
+    #[test]
+    fn reserved_names() {
+        let mut store = AccountStore::default();
+        store.reserve(&["Fenex".to_string()]);
+
+        assert_eq!(Login::WrongPassword, try_login(&mut store, "fenex", b"pwd"));
+        assert_eq!(Login::WrongPassword, try_login(&mut store, "Fenex", b""));
+        assert!(store.get("fenex").is_none(), "cannot be claimed");
+
+        assert!(store.add("Fenex", PasswordHash::new(b"pwd")).unwrap());
+        assert!(!store.add("FENEX", PasswordHash::new(b"pw")).unwrap());
+        assert_eq!(Login::Verified, try_login(&mut store, "fenex", b"pwd"));
+        assert_eq!(Login::WrongPassword, try_login(&mut store, "Fenex", b"pw"));
+    }
+
+    #[test]
+    fn persistence() {
+        let path =
+            std::env::temp_dir().join(format!("vangers-srv-accounts-{}.toml", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let mut store = AccountStore::open(&path).unwrap();
+        assert_eq!(0, store.len());
+        assert_eq!(Login::Claimed, try_login(&mut store, "Fenex", b"pwd"));
+
+        let mut store = AccountStore::open(&path).unwrap();
+        assert_eq!(1, store.len());
+        assert_eq!(Login::Verified, try_login(&mut store, "Fenex", b"pwd"));
+        assert_eq!(Login::WrongPassword, try_login(&mut store, "Fenex", b""));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn login_times_are_flushed() {
+        let path = std::env::temp_dir().join(format!(
+            "vangers-srv-accounts-flush-{}.toml",
+            std::process::id()
+        ));
+        std::fs::write(
+            &path,
+            format!(
+                "[accounts.fenex]\nname = \"Fenex\"\npwd = \"{}\"\nfirst_seen = 1\nlast_seen = 1\n",
+                PasswordHash::new(b"pwd").as_str()
+            ),
+        )
+        .unwrap();
+        let last_seen = || {
+            AccountStore::open(&path)
+                .unwrap()
+                .get("fenex")
+                .unwrap()
+                .last_seen
+        };
+
+        let mut store = AccountStore::open(&path).unwrap();
+        assert_eq!(Login::Verified, try_login(&mut store, "Fenex", b"pwd"));
+        assert_eq!(1, last_seen(), "the file is not rewritten on every login");
+
+        store.flush().unwrap();
+        assert!(last_seen() > 1);
+
+        assert_eq!(Login::Verified, try_login(&mut store, "Fenex", b"pwd"));
+        std::fs::write(&path, "").unwrap();
+        drop(store);
+        assert!(last_seen() > 1, "saved at shutdown");
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn claimed_while_checked() {
+        let mut store = AccountStore::default();
+        let checked = Checked::new(store.pwd_of("Fenex").as_ref(), b"guess");
+
+        assert_eq!(Login::Claimed, try_login(&mut store, "Fenex", b"pwd"));
+        assert_eq!(Login::WrongPassword, store.login("Fenex", checked).unwrap());
+    }
+
+    #[test]
+    fn weak_hashes_are_upgraded() {
+        use ::argon2::password_hash::{PasswordHasher, SaltString};
+        use ::argon2::{Algorithm, Argon2, Params, Version};
+
+        let path = std::env::temp_dir().join(format!(
+            "vangers-srv-accounts-weak-{}.toml",
+            std::process::id()
+        ));
+
+        // the hash of `pwd` made with weaker parameters than the current ones
+        let weak = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(8, 1, 1, None).unwrap(),
+        );
+        let salt = SaltString::encode_b64(b"saltsalt").unwrap();
+        let weak = weak.hash_password(b"pwd", &salt).unwrap().to_string();
+        std::fs::write(
+            &path,
+            format!(
+                "[accounts.fenex]\nname = \"Fenex\"\npwd = \"{}\"\nfirst_seen = 1\nlast_seen = 1\n",
+                weak
+            ),
+        )
+        .unwrap();
+
+        let mut store = AccountStore::open(&path).unwrap();
+        assert_eq!(Login::WrongPassword, try_login(&mut store, "Fenex", b"pw"));
+        assert_eq!(Login::Verified, try_login(&mut store, "Fenex", b"pwd"));
+
+        let store = AccountStore::open(&path).unwrap();
+        let pwd = &store.get("fenex").unwrap().pwd;
+        assert!(!pwd.needs_rehash(), "rehashed");
+        assert!(pwd.verify(b"pwd"));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,155 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use ::tracing::{info, warn};
+
+use crate::game::GameID;
+use crate::utils::DateTime;
+
+#[derive(Debug, ::thiserror::Error)]
+pub enum ChatLogError {
+    #[error("cannot create chat log directory `{0}`: {1}")]
+    CreateDir(PathBuf, std::io::Error),
+    #[error("cannot open chat log file `{0}`: {1}")]
+    Open(PathBuf, std::io::Error),
+    #[error("cannot write chat log file `{0}`: {1}")]
+    Write(PathBuf, std::io::Error),
+}
+
+/// Chat message to log, all texts are UTF-8.
+#[derive(Debug)]
+pub struct ChatEntry<'a> {
+    pub game_id: GameID,
+    pub game_name: &'a str,
+    pub from: &'a str,
+    pub to: &'a [String],
+    pub text: &'a str,
+}
+
+/// Chat messages written to daily files `chat-<date>.log` inside of a directory,
+/// one line per message.
+#[derive(Debug)]
+pub struct ChatLog {
+    dir: PathBuf,
+    /// Date and file of the current day.
+    file: Option<(String, File)>,
+}
+
+impl ChatLog {
+    pub fn open(dir: &Path) -> Result<Self, ChatLogError> {
+        std::fs::create_dir_all(dir).map_err(|err| ChatLogError::CreateDir(dir.to_owned(), err))?;
+
+        Ok(Self {
+            dir: dir.to_owned(),
+            file: None,
+        })
+    }
+
+    /// Appends `entry` to the log of the current day. When the day changes,
+    /// files older than `keep_days` are removed (`0` to keep all files).
+    pub fn write(&mut self, entry: &ChatEntry, keep_days: u32) -> Result<(), ChatLogError> {
+        let now = DateTime::now();
+        let date = now.date();
+
+        if self.file.as_ref().is_none_or(|(d, _)| *d != date) {
+            let path = self.path(&date);
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|err| ChatLogError::Open(path, err))?;
+            self.file = Some((date.clone(), file));
+
+            if keep_days > 0 {
+                self.remove_old(keep_days);
+            }
+        }
+
+        let line = format!(
+            "{} game={} {:?} from={:?} to={:?} text={:?}\n",
+            now, entry.game_id, entry.game_name, entry.from, entry.to, entry.text
+        );
+
+        let (_, file) = self.file.as_mut().expect("the file is opened above");
+        file.write_all(line.as_bytes())
+            .map_err(|err| ChatLogError::Write(self.path(&date), err))
+    }
+
+    fn path(&self, date: &str) -> PathBuf {
+        self.dir.join(format!("chat-{}.log", date))
+    }
+
+    /// Removes log files older than `keep_days`.
+    fn remove_old(&self, keep_days: u32) {
+        let oldest = DateTime::from_unix(
+            crate::utils::unix_now().saturating_sub(u64::from(keep_days) * 86400),
+        )
+        .date();
+
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let Some(date) = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("chat-"))
+                .and_then(|name| name.strip_suffix(".log"))
+            else {
+                continue;
+            };
+
+            // ISO dates are ordered as strings
+            if date < oldest.as_str() {
+                match std::fs::remove_file(entry.path()) {
+                    Ok(_) => info!("removed old chat log {:?}", entry.path()),
+                    Err(err) => warn!("cannot remove old chat log {:?}: {}", entry.path(), err),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn write_and_rotate() {
+        let dir = std::env::temp_dir().join(format!("vangers-srv-chat-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let mut log = ChatLog::open(&dir).unwrap();
+        std::fs::write(dir.join("chat-2000-01-01.log"), "old").unwrap();
+        std::fs::write(dir.join("other.log"), "other").unwrap();
+
+        let to = ["Bob".to_string(), "Вася".to_string()];
+        let entry = ChatEntry {
+            game_id: 1,
+            game_name: "Duel",
+            from: "Fenex",
+            to: &to,
+            text: "привет \"all\"",
+        };
+        log.write(&entry, 30).unwrap();
+        log.write(&entry, 30).unwrap();
+
+        let content = std::fs::read_to_string(log.path(&DateTime::now().date())).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+        assert!(
+            lines[0].ends_with(
+                r#"game=1 "Duel" from="Fenex" to=["Bob", "Вася"] text="привет \"all\"""#
+            ),
+            "{}",
+            lines[0]
+        );
+
+        assert!(!dir.join("chat-2000-01-01.log").exists());
+        assert!(dir.join("other.log").exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -1,11 +1,16 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use std::net::IpAddr;
 
 use ::tokio::io::{AsyncReadExt, AsyncWriteExt};
 use ::tokio::net::TcpStream;
 use ::tokio::sync::mpsc::{self, Receiver};
+use ::tokio::sync::oneshot;
 use ::tracing::{error, info, warn};
 
 use super::protocol::*;
+use super::utils::slice_le_to_i32;
 
 const HS_IN: &[u8] = b"Vivat Sicher, Rock'n'Roll forever!!!";
 const HS_OUT: &[u8] = b"Enter, my son, please...";
@@ -32,28 +37,109 @@ impl PartialEq for Connection {
     }
 }
 
+/// What a queued packet is about, only the latest packet is kept for each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum BatchKey {
+    /// `UPDATE_OBJECT` of the object.
+    Object(i32),
+    /// `PLAYERS_POSITION` of the player.
+    Position(u8),
+}
+
+/// Packets of a client collected between send ticks.
+#[derive(Debug, Default)]
+struct Batch {
+    /// Encoded packets in order of sending, replaced ones are `None`.
+    packets: Vec<Option<Vec<u8>>>,
+    /// Indices in `packets` of the latest state of objects and players.
+    latest: HashMap<BatchKey, usize>,
+}
+
+impl Batch {
+    fn push(&mut self, packet: &Packet) {
+        let key = match packet.action {
+            Action::UPDATE_OBJECT if packet.data.len() >= 4 => {
+                Some(BatchKey::Object(slice_le_to_i32(&packet.data[0..4])))
+            }
+            Action::PLAYERS_POSITION if !packet.data.is_empty() => {
+                Some(BatchKey::Position(packet.data[0]))
+            }
+            _ => None,
+        };
+
+        // the older state is dropped, the newer one is sent in its own turn
+        if let Some(key) = key
+            && let Some(i) = self.latest.insert(key, self.packets.len())
+        {
+            self.packets[i] = None;
+        }
+        self.packets.push(Some(packet.as_bytes()));
+    }
+
+    /// Returns all packets joined together, the batch becomes empty.
+    fn take(&mut self) -> Vec<u8> {
+        self.latest.clear();
+        self.packets.drain(..).flatten().flatten().collect()
+    }
+}
+
 pub struct Client {
     /// Uniq ClientID
     pub id: ClientID,
     pub connection: Connection,
     pub protocol: u8,
+    /// Address the client has connected from.
+    pub addr: Option<IpAddr>,
     tx_server: mpsc::Sender<MpscData>,
     tx_client: mpsc::Sender<Vec<u8>>,
+    /// Packets waiting for `Client::flush`, `None` if packets are sent at once.
+    batch: Option<RefCell<Batch>>,
+    /// Dropped with the client: the socket is not read anymore and it is
+    /// closed once the queued packets are sent.
+    _shutdown: oneshot::Sender<()>,
 }
 
 impl Client {
     pub fn send(&self, packet: &Packet) {
+        match self.batch {
+            Some(ref batch) => batch.borrow_mut().push(packet),
+            None => self.write(packet.as_bytes()),
+        }
+    }
+
+    /// Queues packets until `Client::flush` instead of sending them at once.
+    pub fn enable_batching(&mut self) {
+        self.batch.get_or_insert_default();
+    }
+
+    /// Sends all queued packets in one write.
+    pub fn flush(&self) {
+        let Some(ref batch) = self.batch else {
+            return;
+        };
+
+        let data = batch.borrow_mut().take();
+        if !data.is_empty() {
+            self.write(data);
+        }
+    }
+
+    fn write(&self, data: Vec<u8>) {
         let tx = self.tx_client.clone();
-        let packet = packet.as_bytes();
 
         ::tokio::spawn(async move {
-            if tx.send(packet).await.is_err() {
+            if tx.send(data).await.is_err() {
                 warn!("Error: send data via mpsc (Server => SendClient)");
             }
         });
     }
 
-    fn event_loop(&self, mut stream: TcpStream, mut rx_server: Receiver<Vec<u8>>) {
+    fn event_loop(
+        &self,
+        mut stream: TcpStream,
+        mut rx_server: Receiver<Vec<u8>>,
+        mut shutdown: oneshot::Receiver<()>,
+    ) {
         let tx_server = self.tx_server.clone();
         let id = self.id;
 
@@ -62,7 +148,7 @@ impl Client {
                 Ok(protocol_version) => protocol_version,
                 Err(err) => {
                     info!("auth failed: {}", err);
-                    stream.write(b"Auth failed, bye-bye\0").await.unwrap();
+                    stream.write_all(b"Auth failed, bye-bye\0").await.unwrap();
                     stream.shutdown().await.unwrap();
                     if tx_server
                         .send(MpscData(id, Connection::Disconnected))
@@ -88,7 +174,7 @@ impl Client {
 
             ::tokio::spawn(async move {
                 while let Some(data) = rx_server.recv().await {
-                    if let Err(err) = sw.write(&data).await {
+                    if let Err(err) = sw.write_all(&data).await {
                         error!("client::event_loop: error sending data to client: {err:?}");
                         break;
                     }
@@ -98,7 +184,15 @@ impl Client {
             let mut buff = [0u8; i16::MAX as usize];
             let mut buff_offset: usize = 0;
             loop {
-                match sr.read(&mut buff[buff_offset..]).await {
+                let read = ::tokio::select! {
+                    read = sr.read(&mut buff[buff_offset..]) => read,
+                    _ = &mut shutdown => {
+                        info!("Connection closed by server");
+                        break;
+                    }
+                };
+
+                match read {
                     Ok(0) => {
                         info!("Connection closed by client");
                         tx_server
@@ -184,20 +278,31 @@ impl Client {
     pub fn new(stream: TcpStream, tx: mpsc::Sender<MpscData>) -> Self {
         let id = ::rand::random();
         let (tx_client, rx_server) = mpsc::channel::<Vec<u8>>(1000);
+        let (tx_shutdown, rx_shutdown) = oneshot::channel();
 
         let client = Self {
             protocol: 0,
+            addr: stream.peer_addr().ok().map(|addr| addr.ip()),
             id,
             connection: Connection::Connected,
             tx_server: tx,
             tx_client,
+            batch: None,
+            _shutdown: tx_shutdown,
         };
 
-        client.event_loop(stream, rx_server);
+        client.event_loop(stream, rx_server, rx_shutdown);
         client
     }
 }
 
+impl Drop for Client {
+    /// Queued packets are still sent, e.g. the reason of a kick.
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 enum AuthError {
     #[error("Connection closed by client")]
@@ -239,7 +344,7 @@ async fn auth(stream: &mut TcpStream) -> Result<u8, AuthError> {
                     .copied()
                     .collect::<Vec<_>>();
 
-                if let Err(_) = stream.write(&send).await {
+                if stream.write(&send).await.is_err() {
                     Err(HsResponse)?
                 }
 
@@ -251,3 +356,33 @@ async fn auth(stream: &mut TcpStream) -> Result<u8, AuthError> {
         _ => Err(Connection),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn batch_keeps_latest_state() {
+        let update = |id: i32, time: u8| {
+            let data = [&id.to_le_bytes()[..], &[time]].concat();
+            Packet::new(Action::UPDATE_OBJECT, &data)
+        };
+        let position = |bind_id: u8, x: u8| Packet::new(Action::PLAYERS_POSITION, &[bind_id, x]);
+        let delete = Packet::new(Action::DELETE_OBJECT, &7i32.to_le_bytes());
+
+        let mut batch = Batch::default();
+        batch.push(&update(7, 1));
+        batch.push(&position(1, 1));
+        batch.push(&update(8, 1));
+        batch.push(&delete);
+        batch.push(&update(7, 2));
+        batch.push(&position(1, 2));
+
+        let expected = [update(8, 1), delete, update(7, 2), position(1, 2)]
+            .iter()
+            .flat_map(|p| p.as_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(expected, batch.take());
+        assert!(batch.take().is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+use std::path::PathBuf;
+
+use ::clap::Parser;
+
+/// Command line arguments.
+///
+/// Each value given here (or via its env variable) overrides the value
+/// loaded from the configuration file.
+#[derive(Parser, Debug, Default)]
+#[clap(name = "Vangers Server", version, author)]
+pub struct Args {
+    #[clap(
+        short,
+        long,
+        env = "VANGERS_CONFIG",
+        help = "Path to the TOML configuration file"
+    )]
+    pub config: Option<PathBuf>,
+    #[clap(
+        short,
+        long,
+        env = "VANGERS_PORT",
+        help = "Server port to listening incoming in-game player connections [default: 2197]"
+    )]
+    pub port: Option<u16>,
+    #[clap(
+        long,
+        env = "VANGERS_SUPRESS_LOG_SERVER_TIME",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "Supress log messages for all SERVER_TIME_* events"
+    )]
+    pub supress_log_server_time: Option<bool>,
+    #[clap(
+        long,
+        env = "VANGERS_SUPRESS_LOG_GAMES_LIST_QUERY",
+        num_args = 0..=1,
+        default_missing_value = "true",
+        help = "Supress log messages for all GAMES_LIST_QUERY events"
+    )]
+    pub supress_log_games_list_query: Option<bool>,
+    #[clap(short, long, help = "Enable interactive shell")]
+    pub shell: bool,
+    // #[clap(short, long, help = "Accept incoming connections from localhost only")]
+    // localhost: bool,
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+use ::serde::{Deserialize, Serialize};
+
+use crate::game::MAX_PLAYER_ID;
+
+/// Max count of players in a game.
+///
+/// The capacity of a game is chosen when its creator sends the game name:
+/// the name convention (if enabled) wins over presets, presets win over
+/// `default`. Games created before a reload keep their capacity.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CapacityConfig {
+    /// Capacity of games which match neither a preset nor the name convention.
+    pub default: u8,
+    /// Case-insensitive prefixes of game names with their capacity,
+    /// e.g. `duel = 2` limits game `Duel at noon` to 2 players.
+    pub presets: BTreeMap<String, u8>,
+    /// Take the capacity from a `[N]` tag in the game name, e.g. `Race [4]`.
+    pub from_name: bool,
+}
+
+impl Default for CapacityConfig {
+    fn default() -> Self {
+        Self {
+            default: MAX_PLAYER_ID,
+            presets: BTreeMap::new(),
+            from_name: true,
+        }
+    }
+}
+
+impl CapacityConfig {
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        if !(1..=MAX_PLAYER_ID).contains(&self.default) {
+            problems.push(format!(
+                "`capacity.default`: must be in range [1..{}]",
+                MAX_PLAYER_ID
+            ));
+        }
+
+        for (prefix, &max) in &self.presets {
+            if prefix.is_empty() {
+                problems.push("`capacity.presets`: empty prefix".to_string());
+            }
+            if !(1..=MAX_PLAYER_ID).contains(&max) {
+                problems.push(format!(
+                    "`capacity.presets.{}`: must be in range [1..{}]",
+                    prefix, MAX_PLAYER_ID
+                ));
+            }
+        }
+    }
+
+    /// Returns the capacity of a game with the given `name`.
+    pub fn of(&self, name: &str) -> u8 {
+        if self.from_name
+            && let Some(max) = name_tag(name)
+        {
+            return max;
+        }
+
+        let name = name.to_lowercase();
+        self.presets
+            .iter()
+            .filter(|(prefix, _)| name.starts_with(&prefix.to_lowercase()))
+            // the longest prefix is the most specific one
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, &max)| max)
+            .unwrap_or(self.default)
+    }
+}
+
+/// Returns `N` of the last valid `[N]` tag in `name`.
+fn name_tag(name: &str) -> Option<u8> {
+    name.rsplit('[')
+        .filter_map(|tail| tail.split_once(']'))
+        .filter_map(|(n, _)| n.trim().parse::<u8>().ok())
+        .find(|n| (1..=MAX_PLAYER_ID).contains(n))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn capacity_of() {
+        let conf = CapacityConfig {
+            default: 16,
+            presets: [("duel", 2), ("Race", 4), ("race-big", 8)]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+            from_name: true,
+        };
+
+        assert_eq!(16, conf.of("just a game"));
+        assert_eq!(2, conf.of("DUEL at noon"));
+        assert_eq!(4, conf.of("race around"));
+        assert_eq!(8, conf.of("race-big one"));
+        assert_eq!(6, conf.of("duel [6]"));
+        assert_eq!(3, conf.of("Race [3] [99]"), "out of range tag is ignored");
+        assert_eq!(16, conf.of("game [0] [x]"));
+
+        let conf = CapacityConfig {
+            from_name: false,
+            ..conf
+        };
+        assert_eq!(2, conf.of("duel [6]"));
+    }
+}
@@ -0,0 +1,598 @@
+use std::path::{Path, PathBuf};
+
+use ::serde::{Deserialize, Serialize};
+use ::tracing_subscriber::EnvFilter;
+
+use crate::accounts::AccountStore;
+use crate::protocol::LIMIT_MSG_LEN;
+use crate::utils::{convert_utf8_to_cp866, unknown_placeholders};
+
+use super::{
+    Args, CapacityConfig, GamesListConfig, InterestConfig, ModerationConfig, PrivateGamesConfig,
+};
+
+const DEFAULT_PORT: u16 = 2197;
+
+#[derive(Debug, ::thiserror::Error)]
+pub enum ConfigError {
+    #[error("cannot read config file `{0}`: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("cannot parse config file `{0}`: {1}")]
+    Parse(PathBuf, ::toml::de::Error),
+    #[error("invalid configuration:{}", invalid_errdisplay(.0))]
+    Invalid(Vec<String>),
+}
+
+fn invalid_errdisplay(problems: &[String]) -> String {
+    problems.iter().map(|p| format!("\n  - {}", p)).collect()
+}
+
+/// Effective server configuration.
+///
+/// Built from the TOML configuration file (if any) with command line
+/// arguments and env variables applied on top of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Server port to listening incoming in-game player connections.
+    pub port: u16,
+    pub log: LogConfig,
+    pub motd: MotdConfig,
+    pub games_list: GamesListConfig,
+    pub capacity: CapacityConfig,
+    pub private_games: PrivateGamesConfig,
+    pub accounts: AccountsConfig,
+    pub commands: CommandsConfig,
+    pub chat_log: ChatLogConfig,
+    pub moderation: ModerationConfig,
+    pub vote_kick: VoteKickConfig,
+    pub results: ResultsConfig,
+    pub leaderboards: LeaderboardsConfig,
+    pub interest: InterestConfig,
+    pub batching: BatchingConfig,
+    pub movement: MovementConfig,
+    pub objects: ObjectsConfig,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    /// `tracing` filter directives, used when `RUST_LOG` is not set.
+    pub filter: Option<String>,
+    /// Supress log messages for all SERVER_TIME_* events.
+    pub supress_server_time: bool,
+    /// Supress log messages for all GAMES_LIST_QUERY events.
+    pub supress_games_list_query: bool,
+}
+
+/// Welcome messages sent to a player after a successful `ATTACH_TO_GAME`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MotdConfig {
+    /// Each message is sent as separate system message, see `MotdConfig::PLACEHOLDERS`.
+    pub messages: Vec<String>,
+}
+
+impl MotdConfig {
+    /// Placeholders that may be used inside of `messages`:
+    ///  - `{players}`: count of players in the game of the receiver
+    ///  - `{online}`: count of players on the server
+    ///  - `{games}`: count of games on the server
+    ///  - `{game_id}`: id of the game of the receiver
+    ///  - `{uptime}`: server uptime
+    ///  - `{version}`: server version
+    pub const PLACEHOLDERS: &[&str] =
+        &["players", "online", "games", "game_id", "uptime", "version"];
+}
+
+/// Names reserved by passwords, see `crate::accounts`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccountsConfig {
+    /// Path of the accounts file, accounts are disabled if it is not set.
+    pub path: Option<PathBuf>,
+    /// What to do with a player who uses a reserved name with a wrong password.
+    pub on_mismatch: OnNameMismatch,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnNameMismatch {
+    /// Give the player a random name.
+    #[default]
+    Rename,
+    /// Remove the player from the game.
+    Reject,
+}
+
+/// Chat commands (messages starting with `/`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CommandsConfig {
+    /// Lines shown by `/rules`.
+    pub rules: Vec<String>,
+    /// Names of admins, the names must be reserved (see `AccountsConfig`).
+    pub admins: Vec<String>,
+}
+
+/// Log of all chat messages, see `crate::chat_log`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChatLogConfig {
+    /// Directory of daily log files, the log is disabled if it is not set.
+    pub dir: Option<PathBuf>,
+    /// Log files older than the given days are removed, `0` to keep all files.
+    pub keep_days: u32,
+}
+
+/// Records of finished games, see `crate::results`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ResultsConfig {
+    /// Directory of daily JSON lines files, records are disabled if it is not set.
+    pub dir: Option<PathBuf>,
+}
+
+/// Best times of race modes, see `crate::leaderboards`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LeaderboardsConfig {
+    /// TOML file of leaderboards, they are kept in memory only if it is not set.
+    pub path: Option<PathBuf>,
+    /// Count of places shown by `/top`.
+    pub top: u8,
+}
+
+/// Outbound packets collected and sent once per tick.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BatchingConfig {
+    /// Send tick in milliseconds, packets are sent at once if it is `0`.
+    pub tick_ms: u64,
+}
+
+/// Anti-teleport check of vanger positions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MovementConfig {
+    pub enabled: bool,
+    /// Max speed of a vanger in pixels per second.
+    pub max_speed: u32,
+    /// Distance in pixels allowed on top of `max_speed`.
+    pub tolerance: u32,
+    /// Drop updates with implausible positions, otherwise they are only reported.
+    pub drop_jumps: bool,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_speed: 1500,
+            tolerance: 100,
+            drop_jumps: false,
+        }
+    }
+}
+
+/// Removal of objects left in games.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObjectsConfig {
+    /// Objects which `time` has not advanced for `stale_secs` seconds
+    /// are deleted, the sweep is disabled if it is `0`.
+    pub stale_secs: u64,
+}
+
+impl Default for LeaderboardsConfig {
+    fn default() -> Self {
+        Self { path: None, top: 5 }
+    }
+}
+
+impl Default for ChatLogConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            keep_days: 30,
+        }
+    }
+}
+
+/// Kicking a player out of a game by votes of other players (`/votekick`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct VoteKickConfig {
+    /// Min count of players in a game to start a vote.
+    pub min_players: u8,
+    /// The kick needs votes of more than the given percent of other players.
+    pub majority: u8,
+    /// Time to collect the votes.
+    pub window_secs: u64,
+    /// Time the kicked name cannot rejoin the game.
+    pub ban_minutes: u64,
+}
+
+impl Default for VoteKickConfig {
+    fn default() -> Self {
+        Self {
+            min_players: 3,
+            majority: 50,
+            window_secs: 60,
+            ban_minutes: 10,
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            port: DEFAULT_PORT,
+            log: LogConfig::default(),
+            motd: MotdConfig::default(),
+            games_list: GamesListConfig::default(),
+            capacity: CapacityConfig::default(),
+            private_games: PrivateGamesConfig::default(),
+            accounts: AccountsConfig::default(),
+            commands: CommandsConfig::default(),
+            chat_log: ChatLogConfig::default(),
+            moderation: ModerationConfig::default(),
+            vote_kick: VoteKickConfig::default(),
+            results: ResultsConfig::default(),
+            leaderboards: LeaderboardsConfig::default(),
+            interest: InterestConfig::default(),
+            batching: BatchingConfig::default(),
+            movement: MovementConfig::default(),
+            objects: ObjectsConfig::default(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Loads the configuration file given by `args` (defaults are used
+    /// if there is no file), applies `args` on top of it and validates
+    /// the result.
+    pub fn load(args: &Args) -> Result<Self, ConfigError> {
+        let mut conf = match args.config {
+            Some(ref path) => Self::from_file(path)?,
+            None => Self::default(),
+        };
+
+        conf.apply_args(args);
+        conf.validate()?;
+
+        Ok(conf)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, ConfigError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|err| ConfigError::Read(path.to_owned(), err))?;
+
+        ::toml::from_str(&content).map_err(|err| ConfigError::Parse(path.to_owned(), err))
+    }
+
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(port) = args.port {
+            self.port = port;
+        }
+
+        if let Some(supress) = args.supress_log_server_time {
+            self.log.supress_server_time = supress;
+        }
+        if let Some(supress) = args.supress_log_games_list_query {
+            self.log.supress_games_list_query = supress;
+        }
+    }
+
+    /// Checks the whole configuration and reports every found problem at once.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = vec![];
+
+        if self.port == 0 {
+            problems.push("`port`: must be in range [1..65535]".to_string());
+        }
+
+        if let Some(Err(err)) = self.log.filter.as_deref().map(EnvFilter::try_new) {
+            problems.push(format!("`log.filter`: {}", err));
+        }
+
+        for (i, msg) in self.motd.messages.iter().enumerate() {
+            for key in unknown_placeholders(msg, MotdConfig::PLACEHOLDERS) {
+                problems.push(format!(
+                    "`motd.messages[{}]`: unknown placeholder `{{{}}}`",
+                    i, key
+                ));
+            }
+            if convert_utf8_to_cp866(msg).len() > LIMIT_MSG_LEN {
+                problems.push(format!(
+                    "`motd.messages[{}]`: message is longer than {} bytes",
+                    i,
+                    LIMIT_MSG_LEN - 1
+                ));
+            }
+        }
+
+        for (i, line) in self.commands.rules.iter().enumerate() {
+            if convert_utf8_to_cp866(line).len() > LIMIT_MSG_LEN {
+                problems.push(format!(
+                    "`commands.rules[{}]`: line is longer than {} bytes",
+                    i,
+                    LIMIT_MSG_LEN - 1
+                ));
+            }
+        }
+
+        if self.vote_kick.min_players < 2 {
+            problems.push("`vote_kick.min_players`: must be at least 2".to_string());
+        }
+        if self.vote_kick.majority >= 100 {
+            problems.push("`vote_kick.majority`: must be in range [0..99]".to_string());
+        }
+        if self.vote_kick.window_secs == 0 {
+            problems.push("`vote_kick.window_secs`: must be positive".to_string());
+        }
+        if self.movement.max_speed == 0 {
+            problems.push("`movement.max_speed`: must be positive".to_string());
+        }
+        if self.leaderboards.top == 0 {
+            problems.push("`leaderboards.top`: must be positive".to_string());
+        }
+
+        // admin names are proven by their accounts, which cannot be claimed by a login;
+        // an unreadable accounts file is reported when the server opens it
+        match (&self.accounts.path, self.commands.admins.is_empty()) {
+            (_, true) => {}
+            (None, false) => {
+                problems.push("`commands.admins`: admins require `accounts.path`".to_string())
+            }
+            (Some(path), false) => {
+                if let Ok(accounts) = AccountStore::open(path) {
+                    for (i, admin) in self.commands.admins.iter().enumerate() {
+                        if accounts.get(admin).is_none() {
+                            problems.push(format!(
+                                "`commands.admins[{}]`: `{}` has no account, add it by the shell command `account add`",
+                                i, admin
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        self.games_list.validate(&mut problems);
+        self.capacity.validate(&mut problems);
+        self.private_games.validate(&mut problems);
+        self.moderation.validate(&mut problems);
+        self.interest.validate(&mut problems);
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+
+    /// Returns `tracing` filter: `RUST_LOG` if it is set, otherwise `log.filter`.
+    pub fn log_filter(&self) -> EnvFilter {
+        match (std::env::var_os(EnvFilter::DEFAULT_ENV), &self.log.filter) {
+            (None, Some(filter)) => EnvFilter::new(filter),
+            _ => EnvFilter::from_default_env(),
+        }
+    }
+
+    /// Returns keys which values differ between `self` and `new` but
+    /// cannot be applied without restarting the server.
+    pub fn cold_changes(&self, new: &Self) -> Vec<&'static str> {
+        let mut keys = vec![];
+
+        if self.port != new.port {
+            keys.push("port");
+        }
+        if self.accounts.path != new.accounts.path {
+            keys.push("accounts.path");
+        }
+        if self.chat_log.dir != new.chat_log.dir {
+            keys.push("chat_log.dir");
+        }
+        if self.results.dir != new.results.dir {
+            keys.push("results.dir");
+        }
+        if self.leaderboards.path != new.leaderboards.path {
+            keys.push("leaderboards.path");
+        }
+        if self.batching.tick_ms != new.batching.tick_ms {
+            keys.push("batching.tick_ms");
+        }
+
+        keys
+    }
+
+    /// Returns the effective configuration in TOML format,
+    /// passwords of `private_games.presets` are redacted.
+    pub fn to_toml(&self) -> String {
+        let mut conf = self.clone();
+        conf.private_games.redact();
+        ::toml::to_string_pretty(&conf).unwrap_or_else(|err| format!("# unprintable: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn empty_file_is_default() {
+        let conf: ServerConfig = ::toml::from_str("").unwrap();
+        assert_eq!(DEFAULT_PORT, conf.port);
+        assert!(conf.log.filter.is_none());
+        assert!(!conf.log.supress_server_time);
+        assert!(!conf.log.supress_games_list_query);
+    }
+
+    #[test]
+    fn parse_file() {
+        let conf: ServerConfig = ::toml::from_str(
+            r#"
+            port = 3000
+
+            [log]
+            filter = "debug"
+            supress_server_time = true
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(3000, conf.port);
+        assert_eq!(Some("debug"), conf.log.filter.as_deref());
+        assert!(conf.log.supress_server_time);
+        assert!(!conf.log.supress_games_list_query);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        assert!(::toml::from_str::<ServerConfig>("prot = 3000").is_err());
+        assert!(::toml::from_str::<ServerConfig>("[log]\nfilters = \"info\"").is_err());
+    }
+
+    #[test]
+    fn args_override_file() {
+        let mut conf: ServerConfig =
+            ::toml::from_str("port = 3000\n[log]\nsupress_server_time = true").unwrap();
+
+        conf.apply_args(&Args::default());
+        assert_eq!(3000, conf.port, "no args given, file value is kept");
+        assert!(conf.log.supress_server_time);
+
+        conf.apply_args(&Args {
+            port: Some(4000),
+            supress_log_server_time: Some(false),
+            supress_log_games_list_query: Some(true),
+            ..Default::default()
+        });
+        assert_eq!(4000, conf.port);
+        assert!(!conf.log.supress_server_time, "file value is turned off");
+        assert!(conf.log.supress_games_list_query);
+    }
+
+    #[test]
+    fn args_flags() {
+        use ::clap::Parser;
+
+        let args = Args::parse_from(["vangers-srv"]);
+        assert_eq!(None, args.supress_log_server_time);
+        let args = Args::parse_from(["vangers-srv", "--supress-log-server-time"]);
+        assert_eq!(Some(true), args.supress_log_server_time);
+        let args = Args::parse_from(["vangers-srv", "--supress-log-server-time", "false"]);
+        assert_eq!(Some(false), args.supress_log_server_time);
+    }
+
+    #[test]
+    fn validate_reports_all_problems() {
+        let conf: ServerConfig = ::toml::from_str("port = 0\n[log]\nfilter = \"info,[=\"").unwrap();
+
+        match conf.validate() {
+            Err(ConfigError::Invalid(problems)) => {
+                assert_eq!(2, problems.len(), "{:?}", problems);
+                assert!(problems[0].starts_with("`port`"));
+                assert!(problems[1].starts_with("`log.filter`"));
+            }
+            r => panic!("expected `ConfigError::Invalid`, actual: {:?}", r),
+        }
+
+        assert!(ServerConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_admins() {
+        let path =
+            std::env::temp_dir().join(format!("vangers-srv-admins-{}.toml", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let mut conf = ServerConfig::default();
+        conf.commands.admins = vec!["Fenex".to_string()];
+        assert!(conf.validate().is_err(), "accounts are disabled");
+
+        conf.accounts.path = Some(path.clone());
+        match conf.validate() {
+            Err(ConfigError::Invalid(problems)) => {
+                assert_eq!(1, problems.len(), "{:?}", problems);
+                assert!(problems[0].starts_with("`commands.admins[0]`"));
+            }
+            r => panic!("expected `ConfigError::Invalid`, actual: {:?}", r),
+        }
+
+        let mut accounts = AccountStore::open(&path).unwrap();
+        accounts
+            .add("fenex", crate::player::PasswordHash::new(b"pwd"))
+            .unwrap();
+        assert!(conf.validate().is_ok());
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn validate_motd() {
+        let mut conf = ServerConfig::default();
+        conf.motd.messages = vec![
+            "Welcome! Players: {players}, uptime: {uptime}".to_string(),
+            "{player} {uptime}".to_string(),
+            "x".repeat(LIMIT_MSG_LEN),
+        ];
+
+        match conf.validate() {
+            Err(ConfigError::Invalid(problems)) => {
+                assert_eq!(2, problems.len(), "{:?}", problems);
+                assert!(problems[0].starts_with("`motd.messages[1]`"));
+                assert!(problems[1].starts_with("`motd.messages[2]`"));
+            }
+            r => panic!("expected `ConfigError::Invalid`, actual: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn validate_games_list_and_capacity() {
+        let conf: ServerConfig = ::toml::from_str(
+            "[games_list]\ntitle = \"{name} {players}/{max} {map}\"\ntitle_max_len = 1000\n\
+             [capacity]\ndefault = 31\npresets = { duel = 0 }",
+        )
+        .unwrap();
+
+        match conf.validate() {
+            Err(ConfigError::Invalid(problems)) => {
+                assert_eq!(4, problems.len(), "{:?}", problems);
+                assert!(problems[0].starts_with("`games_list.title`"));
+                assert!(problems[1].starts_with("`games_list.title_max_len`"));
+                assert!(problems[2].starts_with("`capacity.default`"));
+                assert!(problems[3].starts_with("`capacity.presets.duel`"));
+            }
+            r => panic!("expected `ConfigError::Invalid`, actual: {:?}", r),
+        }
+    }
+
+    #[test]
+    fn cold_changes() {
+        let current = ServerConfig::default();
+
+        let mut new = current.clone();
+        new.log.filter = Some("debug".to_string());
+        new.log.supress_server_time = true;
+        assert!(current.cold_changes(&new).is_empty());
+
+        new.port = current.port + 1;
+        new.accounts.path = Some("accounts.toml".into());
+        assert_eq!(vec!["port", "accounts.path"], current.cold_changes(&new));
+    }
+
+    #[test]
+    fn effective_config_roundtrip() {
+        let mut conf = ServerConfig::default();
+        conf.private_games
+            .presets
+            .insert("clan".to_string(), "secret".to_string());
+
+        let toml = conf.to_toml();
+        assert!(!toml.contains("secret"), "{}", toml);
+        let conf: ServerConfig = ::toml::from_str(&toml).unwrap();
+        assert_eq!(DEFAULT_PORT, conf.port);
+    }
+}
@@ -0,0 +1,127 @@
+use ::serde::{Deserialize, Serialize};
+
+use crate::game::Type as GameType;
+use crate::utils::unknown_placeholders;
+
+/// Appearance of the games in `GAMES_LIST_RESPONSE`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GamesListConfig {
+    /// Title of each game, see `GamesListConfig::PLACEHOLDERS`.
+    pub title: String,
+    /// Max length of the title in bytes (including null-terminator),
+    /// the tail of a longer title is replaced by `...`.
+    pub title_max_len: usize,
+    /// Letters of game types used by the `{type}` placeholder.
+    pub types: GameTypeLetters,
+    /// Marker of private games used by the `{lock}` placeholder.
+    pub lock: String,
+    /// Marker of games closed for new players used by the `{closed}` placeholder.
+    pub closed: String,
+    /// Do not show games without free player slots.
+    pub hide_full: bool,
+    /// Do not show games older than the given hours.
+    pub max_age_hours: Option<u32>,
+    pub sort: GamesListSort,
+}
+
+impl GamesListConfig {
+    /// Placeholders that may be used inside of `title`:
+    ///  - `{name}`: game name given by its creator
+    ///  - `{lock}`: `lock` marker if the game is private, otherwise empty
+    ///  - `{closed}`: `closed` marker if the game is closed for new players, otherwise empty
+    ///  - `{players}`: count of players in the game
+    ///  - `{max}`: max count of players in the game
+    ///  - `{type}`: game type letter (see `types`)
+    ///  - `{time}`: time since the game was created
+    ///  - `{id}`: game id
+    pub const PLACEHOLDERS: &[&str] = &[
+        "name", "lock", "closed", "players", "max", "type", "time", "id",
+    ];
+
+    /// Valid range of `title_max_len`.
+    pub const TITLE_MAX_LEN_RANGE: std::ops::RangeInclusive<usize> = 16..=255;
+
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        for key in unknown_placeholders(&self.title, Self::PLACEHOLDERS) {
+            problems.push(format!(
+                "`games_list.title`: unknown placeholder `{{{}}}`",
+                key
+            ));
+        }
+
+        if !Self::TITLE_MAX_LEN_RANGE.contains(&self.title_max_len) {
+            problems.push(format!(
+                "`games_list.title_max_len`: must be in range {:?}",
+                Self::TITLE_MAX_LEN_RANGE
+            ));
+        }
+    }
+}
+
+impl Default for GamesListConfig {
+    fn default() -> Self {
+        Self {
+            title: "[Rust-SRV] {lock}{closed}{name}: {players}/{max} {type} {time}".to_string(),
+            title_max_len: 128,
+            types: GameTypeLetters::default(),
+            lock: "[locked] ".to_string(),
+            closed: "[closed] ".to_string(),
+            hide_full: false,
+            max_age_hours: None,
+            sort: GamesListSort::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameTypeLetters {
+    pub van_war: String,
+    pub mechosoma: String,
+    pub passembloss: String,
+    pub mir_rage: String,
+    pub huntage: String,
+    pub mustodont: String,
+}
+
+impl GameTypeLetters {
+    pub fn get(&self, gmtype: GameType) -> &str {
+        match gmtype {
+            GameType::VAN_WAR => &self.van_war,
+            GameType::MECHOSOMA => &self.mechosoma,
+            GameType::PASSEMBLOSS => &self.passembloss,
+            GameType::MIR_RAGE => &self.mir_rage,
+            GameType::HUNTAGE => &self.huntage,
+            GameType::MUSTODONT => &self.mustodont,
+            GameType::UNCONFIGURED => "?",
+        }
+    }
+}
+
+impl Default for GameTypeLetters {
+    fn default() -> Self {
+        Self {
+            van_war: "V".to_string(),
+            mechosoma: "M".to_string(),
+            passembloss: "P".to_string(),
+            mir_rage: "R".to_string(),
+            huntage: "H".to_string(),
+            mustodont: "U".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamesListSort {
+    /// Older games first.
+    #[default]
+    Id,
+    /// Newer games first.
+    Newest,
+    /// Games with more players first.
+    Players,
+    /// Games ordered by their names.
+    Name,
+}
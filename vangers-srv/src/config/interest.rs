@@ -0,0 +1,76 @@
+use ::serde::{Deserialize, Serialize};
+
+use crate::vanject::NID;
+
+/// Thinning out of `UPDATE_OBJECT` for far receivers, see `Game::near_receivers`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct InterestConfig {
+    pub enabled: bool,
+    /// Distance (in world pixels, added to the radius of the object) within
+    /// which a receiver gets every update of the object.
+    pub radius: u32,
+    /// Receivers out of `radius` get every `far_rate`-th update only.
+    pub far_rate: u32,
+    /// Object types which updates are always sent to everyone, see `NID::NAMES`.
+    pub always: Vec<String>,
+}
+
+impl Default for InterestConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            radius: 1000,
+            far_rate: 5,
+            always: vec!["vanger".to_string()],
+        }
+    }
+}
+
+impl InterestConfig {
+    /// Returns `true` if updates of objects of `nid` type are always sent to everyone.
+    pub fn is_always(&self, nid: i32) -> bool {
+        self.always
+            .iter()
+            .any(|name| NID::by_name(name) == Some(nid))
+    }
+
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        if self.far_rate == 0 {
+            problems.push("`interest.far_rate`: must be positive".to_string());
+        }
+
+        for (i, name) in self.always.iter().enumerate() {
+            if NID::by_name(name).is_none() {
+                let names = NID::NAMES.map(|(name, _)| name).join(", ");
+                problems.push(format!(
+                    "`interest.always[{}]`: unknown object type `{}`, expected one of: {}",
+                    i, name, names
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let conf = InterestConfig {
+            far_rate: 0,
+            always: vec!["shell".to_string(), "mechos".to_string()],
+            ..Default::default()
+        };
+
+        let mut problems = vec![];
+        conf.validate(&mut problems);
+        assert_eq!(2, problems.len(), "{:?}", problems);
+        assert!(problems[0].starts_with("`interest.far_rate`"));
+        assert!(problems[1].starts_with("`interest.always[1]`"));
+
+        assert!(conf.is_always(NID::SHELL));
+        assert!(!conf.is_always(NID::VANGER));
+    }
+}
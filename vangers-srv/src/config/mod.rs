@@ -0,0 +1,17 @@
+mod args;
+mod capacity;
+mod config;
+mod games_list;
+mod interest;
+mod moderation;
+mod private_games;
+mod reload;
+
+pub use args::*;
+pub use capacity::*;
+pub use config::*;
+pub use games_list::*;
+pub use interest::*;
+pub use moderation::*;
+pub use private_games::*;
+pub use reload::*;
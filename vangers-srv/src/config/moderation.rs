@@ -0,0 +1,51 @@
+use ::serde::{Deserialize, Serialize};
+
+/// Chat moderation, see `crate::server::moderation`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModerationConfig {
+    /// Case-insensitive words replaced by `*` in chat messages.
+    pub words: Vec<String>,
+    /// Case-insensitive words delivered as is, but reported to admins.
+    pub watch: Vec<String>,
+    /// Identical messages allowed per `repeat_window_secs`, the next ones
+    /// are dropped. `0` to disable.
+    pub repeat_limit: u32,
+    pub repeat_window_secs: u64,
+    /// Names of players who cannot chat (the `/mute` command adds more).
+    pub muted: Vec<String>,
+    /// Report dropped and flagged messages to admins online.
+    pub report: bool,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            words: vec![],
+            watch: vec![],
+            repeat_limit: 3,
+            repeat_window_secs: 30,
+            muted: vec![],
+            report: true,
+        }
+    }
+}
+
+impl ModerationConfig {
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        for (key, words) in [("words", &self.words), ("watch", &self.watch)] {
+            for (i, word) in words.iter().enumerate() {
+                if word.trim().is_empty() {
+                    problems.push(format!("`moderation.{}[{}]`: empty word", key, i));
+                }
+            }
+        }
+
+        if self.repeat_limit > 0 && self.repeat_window_secs == 0 {
+            problems.push(
+                "`moderation.repeat_window_secs`: must be positive if `repeat_limit` is set"
+                    .to_string(),
+            );
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use ::serde::{Deserialize, Serialize};
+
+/// Password-protected games.
+///
+/// Players joining a private game must send its password with their
+/// `REGISTER_NAME`, otherwise they are removed from the game.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PrivateGamesConfig {
+    /// Case-insensitive tag in the game name which makes the game private,
+    /// the password is the one the creator registered with. Empty to disable.
+    pub tag: String,
+    /// Case-insensitive prefixes of game names with their passwords.
+    pub presets: BTreeMap<String, String>,
+}
+
+impl Default for PrivateGamesConfig {
+    fn default() -> Self {
+        Self {
+            tag: "[private]".to_string(),
+            presets: BTreeMap::new(),
+        }
+    }
+}
+
+/// Shown instead of the passwords of `presets`.
+const REDACTED: &str = "<redacted>";
+
+/// Passwords of `presets` are not printed.
+impl std::fmt::Debug for PrivateGamesConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivateGamesConfig")
+            .field("tag", &self.tag)
+            .field("presets", &self.presets.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+/// Who sets the password of a game.
+#[derive(Debug, PartialEq, Eq)]
+pub enum GameAccess<'a> {
+    Public,
+    /// The password of the game creator.
+    Creator,
+    /// The password from `PrivateGamesConfig::presets`.
+    Preset(&'a str),
+}
+
+impl PrivateGamesConfig {
+    pub fn validate(&self, problems: &mut Vec<String>) {
+        for (prefix, pwd) in &self.presets {
+            if prefix.is_empty() {
+                problems.push("`private_games.presets`: empty prefix".to_string());
+            }
+            if pwd.is_empty() {
+                problems.push(format!(
+                    "`private_games.presets.{}`: empty password",
+                    prefix
+                ));
+            }
+        }
+    }
+
+    /// Replaces the passwords of `presets` to print the configuration.
+    pub fn redact(&mut self) {
+        self.presets
+            .values_mut()
+            .for_each(|pwd| *pwd = REDACTED.to_string());
+    }
+
+    /// Returns access to a game with the given `name`, presets win over the tag.
+    pub fn of(&self, name: &str) -> GameAccess<'_> {
+        let name = name.to_lowercase();
+
+        let preset = self
+            .presets
+            .iter()
+            .filter(|(prefix, _)| name.starts_with(&prefix.to_lowercase()))
+            .max_by_key(|(prefix, _)| prefix.len());
+
+        match preset {
+            Some((_, pwd)) => GameAccess::Preset(pwd),
+            None if !self.tag.is_empty() && name.contains(&self.tag.to_lowercase()) => {
+                GameAccess::Creator
+            }
+            None => GameAccess::Public,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn access_of() {
+        let mut conf = PrivateGamesConfig::default();
+        conf.presets
+            .insert("Clan".to_string(), "secret".to_string());
+
+        assert_eq!(GameAccess::Public, conf.of("just a game"));
+        assert_eq!(GameAccess::Creator, conf.of("duel [PRIVATE]"));
+        assert_eq!(GameAccess::Preset("secret"), conf.of("clan war [private]"));
+
+        conf.tag.clear();
+        assert_eq!(GameAccess::Public, conf.of("duel [private]"));
+    }
+
+    #[test]
+    fn passwords_are_not_printed() {
+        let mut conf = PrivateGamesConfig::default();
+        conf.presets
+            .insert("Clan".to_string(), "secret".to_string());
+        assert!(!format!("{:?}", conf).contains("secret"));
+
+        conf.redact();
+        assert_eq!(REDACTED, conf.presets["Clan"]);
+    }
+}
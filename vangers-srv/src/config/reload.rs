@@ -0,0 +1,86 @@
+use ::tracing::{info, warn};
+use ::tracing_subscriber::{EnvFilter, Registry, reload};
+
+use super::{Args, ConfigError, ServerConfig};
+
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+/// Re-reads the configuration at runtime (SIGHUP or `reload` shell command).
+pub struct Reloader {
+    args: Args,
+    log_filter: LogFilterHandle,
+}
+
+impl Reloader {
+    pub fn new(args: Args, log_filter: LogFilterHandle) -> Self {
+        Self { args, log_filter }
+    }
+
+    /// Loads the configuration again and applies everything that can be
+    /// changed at runtime. Settings that require a restart keep the values
+    /// of `current` and are reported.
+    ///
+    /// `current` stays untouched if the new configuration is invalid.
+    pub fn reload(&self, current: &ServerConfig) -> Result<ServerConfig, ConfigError> {
+        let mut conf = ServerConfig::load(&self.args)?;
+
+        for key in current.cold_changes(&conf) {
+            warn!(
+                "config reload: `{}` cannot be changed at runtime, restart is required",
+                key
+            );
+        }
+        conf.port = current.port;
+        conf.accounts.path = current.accounts.path.clone();
+        conf.chat_log.dir = current.chat_log.dir.clone();
+        conf.results.dir = current.results.dir.clone();
+        conf.leaderboards.path = current.leaderboards.path.clone();
+        conf.batching.tick_ms = current.batching.tick_ms;
+
+        if conf.log.filter != current.log.filter {
+            if std::env::var_os(EnvFilter::DEFAULT_ENV).is_some() {
+                warn!(
+                    "config reload: `log.filter` is ignored because `{}` is set",
+                    EnvFilter::DEFAULT_ENV
+                );
+            } else if let Err(err) = self.log_filter.reload(conf.log_filter()) {
+                warn!("config reload: cannot apply `log.filter`: {}", err);
+            }
+        }
+
+        info!("configuration reloaded");
+
+        Ok(conf)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn reloader(config: &std::path::Path) -> Reloader {
+        let (_, handle) = reload::Layer::<_, Registry>::new(EnvFilter::new("info"));
+        let args = Args {
+            config: Some(config.to_owned()),
+            ..Default::default()
+        };
+        Reloader::new(args, handle)
+    }
+
+    #[test]
+    fn reload_keeps_cold_settings() {
+        let path =
+            std::env::temp_dir().join(format!("vangers-srv-reload-{}.toml", std::process::id()));
+        std::fs::write(&path, "port = 3000\n[log]\nsupress_server_time = true").unwrap();
+
+        let current = ServerConfig::default();
+        let conf = reloader(&path).reload(&current).unwrap();
+        assert_eq!(current.port, conf.port, "port cannot be changed at runtime");
+        assert!(conf.log.supress_server_time);
+
+        std::fs::write(&path, "port = 0").unwrap();
+        assert!(reloader(&path).reload(&current).is_err());
+
+        std::fs::remove_file(&path).ok();
+    }
+}
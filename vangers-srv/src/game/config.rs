@@ -1,6 +1,7 @@
 use std::mem::size_of;
 
 use ::num_traits::FromPrimitive;
+use ::serde::Serialize;
 
 use crate::protocol::{NetTransportReceive, NetTransportSend};
 use crate::utils::slice_le_to_i32;
@@ -8,7 +9,8 @@ use crate::utils::slice_le_to_i32;
 use super::Type;
 use super::prm::*;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(tag = "mode", content = "params", rename_all = "snake_case")]
 pub enum GameMode {
     VanWar(VanWar),
     Mechosoma(Mechosoma),
@@ -18,7 +20,7 @@ pub enum GameMode {
     Mustodont(Mustodont),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Config {
     pub initial_rnd: i32,
     pub initial_cash: i32,
@@ -165,8 +167,7 @@ mod test {
                 assert_eq!(8, s.team_mode);
                 assert_eq!(9, s.world_access);
                 assert_eq!(10, s.max_kills);
-                // TODO: do not forget uncomment the line when max_time will be fixed
-                // assert_eq!(11, s.max_time);
+                assert_eq!(11, s.max_time);
             }
             gm => panic!("missmatch gamemode, expected: `WanWar`, actual: {:?}", gm),
         };
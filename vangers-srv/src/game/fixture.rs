@@ -0,0 +1,45 @@
+//! Games and objects shared by tests.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::client::ClientID;
+use crate::player::Player;
+use crate::vanject::{Pos, Vanject};
+
+use super::{Game, World};
+
+/// Creates the game `1` of players `names` (`client_id` from `11`).
+pub fn game(names: &[&str]) -> Game {
+    let mut game = Game::new(1);
+    for (i, name) in names.iter().enumerate() {
+        let mut player = Player::new(11 + i);
+        player.set_auth(format!("{}\0", name).as_bytes(), None);
+        game.attach_player(player);
+    }
+    game
+}
+
+/// Places players of `game` into the world `0` at given positions.
+pub fn place(game: &mut Game, positions: &[(ClientID, Pos<i16>)]) {
+    let world = World::new(0, 16384);
+    if game.worlds.is_empty() {
+        game.worlds
+            .push(Rc::new(RefCell::new(World::new(0, 16384))));
+    }
+    for &(client_id, pos) in positions {
+        game.get_mut_player(client_id).unwrap().pos = pos;
+        game.place_player(client_id, &world);
+    }
+}
+
+/// Adds an object of the type `nid` (see `NID`) of the station `1` in the world `0`
+/// owned by the player `1` to `game`, returns its id.
+pub fn add_vanject(game: &mut Game, nid: i32) -> i32 {
+    let id = (1 << 26) | nid | 1;
+    let create = [&id.to_le_bytes()[..], &[0; 11]].concat();
+    let mut vanject = Vanject::create_from_slice(&create).unwrap();
+    vanject.player_bind_id = 1;
+    game.vanjects.insert(id, vanject);
+    id
+}
@@ -2,23 +2,68 @@ use std::borrow::Cow;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::ffi::CStr;
+use std::net::IpAddr;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 use crate::client::ClientID;
-use crate::player::{Player, Status as PlayerStatus};
+use crate::player::{PasswordHash, Player, Status as PlayerStatus};
 use crate::protocol::NetTransportReceive;
-use crate::utils::Uptime;
-use crate::vanject::Vanject;
+use crate::utils::{Uptime, convert_cp866_to_utf8};
+use crate::vanject::{Vanject, get_station, is_owned_vanject};
 
 use super::Config;
 use super::Type;
 use super::World;
+use super::{MatchResult, PlayerRecord};
 
-const MAX_PLAYER_ID: u8 = 30u8; // or 31 (?)
+/// Max count of players in a game supported by the protocol.
+pub const MAX_PLAYER_ID: u8 = 30u8; // or 31 (?)
 const MIN_PLAYER_ID: u8 = 1u8;
 
 pub type GameID = u32;
 
+/// Password protection of a private game.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Lock {
+    /// The password is the `REGISTER_NAME` password of the creator,
+    /// who has not registered yet.
+    Creator(ClientID),
+    /// Players must register with the password of this hash.
+    Password(PasswordHash),
+    /// Players must register with this password from `private_games.presets`.
+    Preset(String),
+}
+
+/// The preset password is not printed.
+impl std::fmt::Debug for Lock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Creator(client_id) => f.debug_tuple("Creator").field(client_id).finish(),
+            Self::Password(hash) => f.debug_tuple("Password").field(hash).finish(),
+            Self::Preset(_) => f.write_str("Preset(..)"),
+        }
+    }
+}
+
+/// What a ban of a kicked player is keyed by, see `Game::ban`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Ban {
+    /// Lowercase name of the player.
+    Name(String),
+    /// Address the player has connected from, so a new name does not help.
+    Addr(IpAddr),
+}
+
+/// Running vote to kick a player out of the game, see `/votekick`.
+#[derive(Debug, Clone)]
+pub struct VoteKick {
+    pub target: ClientID,
+    /// Players who voted for the kick.
+    pub votes: Vec<ClientID>,
+    pub until: Instant,
+}
+
 pub struct Game {
     pub id: GameID,
     pub name: Vec<u8>,
@@ -27,6 +72,26 @@ pub struct Game {
     pub birth_time: Uptime,
     pub config: Option<Config>,
     pub vanjects: HashMap<i32, Vanject>,
+    /// Max count of players in the game, `MAX_PLAYER_ID` at most.
+    pub max_players: u8,
+    /// `Some` if the game is private.
+    pub lock: Option<Lock>,
+    /// Player who manages the game: the creator, then the next player
+    /// if the host leaves.
+    pub host: Option<ClientID>,
+    /// New players are not attached to a closed game (`/close`),
+    /// unlike `lock` it has nothing to do with passwords.
+    pub closed: bool,
+    pub vote_kick: Option<VoteKick>,
+    /// Names and addresses of kicked players, who cannot rejoin the game
+    /// until the given time.
+    pub bans: HashMap<Ban, Instant>,
+    /// Time since the first player has entered a world.
+    pub match_clock: Option<Uptime>,
+    /// `Some` if the match is over.
+    pub result: Option<MatchResult>,
+    /// Players who have left the game, kept for its record.
+    pub left_players: Vec<PlayerRecord>,
     // Bitwise field. Used to marks which players' ids already taken.
     // Each bit marks its own player.
     // used_players_ids: u32
@@ -44,6 +109,15 @@ impl std::fmt::Debug for Game {
             .field("name", &name)
             .field("birth_time", &self.birth_time)
             .field("players", &self.players)
+            .field("max_players", &self.max_players)
+            .field("locked", &self.is_locked())
+            .field("host", &self.host)
+            .field("closed", &self.closed)
+            .field("vote_kick", &self.vote_kick)
+            .field("bans", &self.bans)
+            .field("match_clock", &self.match_clock)
+            .field("result", &self.result)
+            .field("left_players", &self.left_players)
             .field("worlds", &self.worlds)
             .field("config", &self.config)
             .field("vanjects_count", &self.vanjects.len())
@@ -65,6 +139,15 @@ impl Game {
             birth_time: Uptime::new(),
             config: None, // used_players_ids: 0,
             vanjects: HashMap::new(),
+            max_players: MAX_PLAYER_ID,
+            lock: None,
+            host: None,
+            closed: false,
+            vote_kick: None,
+            bans: HashMap::new(),
+            match_clock: None,
+            result: None,
+            left_players: vec![],
         }
     }
 
@@ -76,8 +159,59 @@ impl Game {
         self.players.iter_mut().find(|p| p.client_id == client_id)
     }
 
+    /// Returns the name of the game decoded from CP866.
+    pub fn name_utf8(&self) -> String {
+        let name = match self.name.last() {
+            Some(0) => &self.name[..self.name.len() - 1],
+            _ => &self.name[..],
+        };
+        convert_cp866_to_utf8(name).unwrap_or_default()
+    }
+
+    /// Returns `true` if the game is private (password protected).
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some()
+    }
+
+    /// Returns `false` if the player `client_id` has not passed the password
+    /// check of the private game (`REGISTER_NAME`) yet.
+    pub fn is_admitted(&self, client_id: ClientID) -> bool {
+        self.lock.is_none() || self.get_player(client_id).is_some_and(|p| p.auth.is_some())
+    }
+
+    /// Returns `true` if the name (UTF-8) or the address is banned in the game now.
+    pub fn is_banned(&self, name: Option<&str>, addr: Option<IpAddr>) -> bool {
+        let now = Instant::now();
+        let name = name.map(|name| Ban::Name(name.to_lowercase()));
+        let addr = addr.map(Ban::Addr);
+
+        [name, addr]
+            .into_iter()
+            .flatten()
+            .any(|ban| self.bans.get(&ban).is_some_and(|&until| now < until))
+    }
+
+    /// Bans the name (UTF-8) and the address in the game until the given time.
+    pub fn ban(&mut self, name: &str, addr: Option<IpAddr>, until: Instant) {
+        let now = Instant::now();
+        self.bans.retain(|_, &mut until| now < until);
+        self.bans.insert(Ban::Name(name.to_lowercase()), until);
+        if let Some(addr) = addr {
+            self.bans.insert(Ban::Addr(addr), until);
+        }
+    }
+
+    /// Returns `true` if the game has no free player slots.
+    pub fn is_full(&self) -> bool {
+        self.get_uniq_player_id().is_none()
+    }
+
     /// Returns new unique `player_id` if the game has free player slots.
     fn get_uniq_player_id(&self) -> Option<u8> {
+        if self.players.len() >= self.max_players as usize {
+            return None;
+        }
+
         let mut ids = self
             .players
             .iter()
@@ -184,6 +318,7 @@ impl Game {
             self.worlds.iter().find(|w| w.borrow().id == world.id),
         ) {
             p.world = Some(Rc::clone(w));
+            self.match_clock.get_or_insert_with(Uptime::new);
             if p.status != PlayerStatus::GAMING {
                 p.status = PlayerStatus::GAMING;
                 true
@@ -195,12 +330,72 @@ impl Game {
         }
     }
 
+    /// Returns other players whose clients need the object `object_id`,
+    /// see `Player::sees_object`.
+    pub fn object_receivers(&self, client_id: ClientID, object_id: i32) -> Vec<ClientID> {
+        self.players
+            .iter()
+            .filter(|p| p.client_id != client_id && p.sees_object(object_id))
+            .map(|p| p.client_id)
+            .collect()
+    }
+
+    /// Returns the bind id of the player who owns the object `object_id`:
+    /// its creator, or the station of the id if the object is unknown.
+    /// `None` if any player may change the object, see `is_owned_vanject`.
+    pub fn object_owner(&self, object_id: i32) -> Option<u8> {
+        if !is_owned_vanject(object_id) {
+            return None;
+        }
+
+        match self.vanjects.get(&object_id) {
+            Some(vanject) => Some(vanject.player_bind_id),
+            None => Some(get_station(object_id) as u8),
+        }
+    }
+
+    /// Returns objects of the player `player_bind_id` which must not
+    /// outlive the player, see `Game::object_owner`.
+    pub fn owned_objects(&self, player_bind_id: u8) -> Vec<i32> {
+        self.vanjects
+            .keys()
+            .copied()
+            .filter(|&id| self.object_owner(id) == Some(player_bind_id))
+            .collect()
+    }
+
+    /// Returns objects which `time` has not advanced for `max_age` by `now`:
+    /// objects anyone may take (stuff on the ground, global objects) and objects
+    /// left by players. Objects of players in the game (see `Game::object_owner`)
+    /// are kept, e.g. an unused inventory.
+    pub fn stale_objects(&self, now: Instant, max_age: Duration) -> Vec<i32> {
+        let binds = self
+            .players
+            .iter()
+            .filter_map(|p| p.bind.map(|bind| bind.id()))
+            .collect::<Vec<_>>();
+
+        self.vanjects
+            .values()
+            .filter(|v| now.saturating_duration_since(v.advanced) >= max_age)
+            .filter(|v| {
+                !self
+                    .object_owner(v.id)
+                    .is_some_and(|id| binds.contains(&id))
+            })
+            .map(|v| v.id)
+            .collect()
+    }
+
     /// Try to attach `p` player to the game.
-    /// Returns attached player's id if player was attached sucessfully, otherwise `0`.
+    /// Returns attached player's id if player was attached sucessfully,
+    /// otherwise `None` (the game has no free player slots).
+    /// The first attached player becomes the host.
     pub fn attach_player(&mut self, mut p: Player) -> Option<u8> {
         match self.get_uniq_player_id() {
             Some(uniq_id) if uniq_id > 0 => {
                 p.set_bind(uniq_id);
+                self.host.get_or_insert(p.client_id);
                 self.players.push(p);
                 Some(uniq_id)
             }
@@ -208,6 +403,21 @@ impl Game {
         }
     }
 
+    /// Removes the player `client_id` from the game. If it was the host,
+    /// the host status passes to the next player, who is returned.
+    pub fn detach_player(&mut self, client_id: ClientID) -> Option<ClientID> {
+        if let Some(record) = self.get_player(client_id).and_then(PlayerRecord::of) {
+            self.left_players.push(record);
+        }
+        self.players.retain(|p| p.client_id != client_id);
+
+        if self.host != Some(client_id) {
+            return None;
+        }
+        self.host = self.players.first().map(|p| p.client_id);
+        self.host
+    }
+
     pub fn set_config(&mut self, slice: &[u8]) -> Result<(), &'static str> {
         if self.is_configured() {
             return Err("already configured");
@@ -220,7 +430,6 @@ impl Game {
         }
     }
 
-    #[allow(dead_code)]
     pub fn get_gmtype(&self) -> Type {
         match self.config {
             Some(ref a) => a.get_gametype(),
@@ -228,3 +437,102 @@ impl Game {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::fixture;
+    use crate::vanject::NID;
+
+    #[test]
+    fn object_receivers() {
+        let mut game = Game::new(1);
+        for client_id in [11, 12, 13, 14] {
+            game.attach_player(Player::new(client_id));
+        }
+        for world_id in [0, 1] {
+            game.worlds
+                .push(Rc::new(RefCell::new(World::new(world_id, 16384))));
+        }
+        game.place_player(11, &World::new(0, 16384));
+        game.place_player(12, &World::new(0, 16384));
+        game.place_player(13, &World::new(1, 16384));
+
+        // a device of the station 1 in the world 0, then in the world 1
+        let fostral = i32::from_le_bytes([1, 0, 2, 4]);
+        let glorx = i32::from_le_bytes([1, 0, 66, 4]);
+        let global = i32::from_le_bytes([83, 0, 64, 0]);
+
+        assert_eq!(vec![12], game.object_receivers(11, fostral));
+        assert_eq!(vec![13], game.object_receivers(11, glorx));
+        assert_eq!(vec![12, 13, 14], game.object_receivers(11, global));
+    }
+
+    #[test]
+    fn object_owner() {
+        let mut game = Game::new(1);
+
+        // a mechos and a device of the station 1, a stuff on the ground
+        let mechos = i32::from_le_bytes([1, 0, 9, 4]);
+        let device = i32::from_le_bytes([1, 0, 2, 4]);
+        let stuff = i32::from_le_bytes([1, 0, 11, 4]);
+        assert_eq!(Some(1), game.object_owner(mechos));
+        assert_eq!(Some(1), game.object_owner(device));
+        assert_eq!(None, game.object_owner(stuff));
+
+        let device = fixture::add_vanject(&mut game, NID::DEVICE);
+        game.vanjects.get_mut(&device).unwrap().player_bind_id = 2;
+        assert_eq!(Some(2), game.object_owner(device), "the creator owns it");
+    }
+
+    #[test]
+    fn owned_and_stale_objects() {
+        let mut game = Game::new(1);
+
+        // a mechos and a device of the station 1, a stuff on the ground
+        let mechos = fixture::add_vanject(&mut game, NID::VANGER);
+        let device = fixture::add_vanject(&mut game, NID::DEVICE);
+        let stuff = fixture::add_vanject(&mut game, NID::STUFF);
+
+        let mut owned = game.owned_objects(1);
+        owned.sort();
+        assert_eq!(vec![device, mechos], owned);
+        assert!(game.owned_objects(2).is_empty());
+
+        // the mechos is moving, others are lying still
+        let later = game.vanjects[&mechos].advanced + Duration::from_secs(60);
+        game.vanjects.get_mut(&mechos).unwrap().advanced = later;
+
+        let now = later + Duration::from_secs(1);
+        let mut stale = game.stale_objects(now, Duration::from_secs(60));
+        stale.sort();
+        assert_eq!(vec![device, stuff], stale, "the owner has left");
+        assert!(game.stale_objects(now, Duration::from_secs(120)).is_empty());
+
+        game.attach_player(Player::new(11));
+        assert_eq!(
+            vec![stuff],
+            game.stale_objects(now, Duration::from_secs(60)),
+            "the owner is in the game"
+        );
+    }
+
+    #[test]
+    fn lock_debug() {
+        let lock = Lock::Preset("secret".to_string());
+        assert_eq!("Preset(..)", format!("{:?}", lock));
+    }
+
+    #[test]
+    fn admission() {
+        let mut game = Game::new(1);
+        game.attach_player(Player::new(11));
+        assert!(game.is_admitted(11), "not private");
+
+        game.lock = Some(Lock::Password(PasswordHash::new(b"secret")));
+        assert!(!game.is_admitted(11));
+
+        game.get_mut_player(11).unwrap().set_auth(b"Fenex\0", None);
+        assert!(game.is_admitted(11));
+    }
+}
@@ -1,11 +1,17 @@
 mod config;
+#[cfg(test)]
+pub mod fixture;
 mod game;
 mod prm;
+mod result;
+mod team;
 mod world;
 
 pub use config::*;
 pub use game::*;
 pub use prm::*;
+pub use result::*;
+pub use team::*;
 pub use world::*;
 
 use enum_primitive_derive::Primitive;
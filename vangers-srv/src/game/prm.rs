@@ -1,12 +1,16 @@
+use ::serde::Serialize;
+
 use crate::utils::slice_le_to_i32;
 // use std::convert::TryInto;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct VanWar {
     pub nascency: i32, // Bit-wise using
     pub team_mode: i32,
     pub world_access: i32, // 0 - all worlds, 1 - one world...
+    /// Kills to win the match, `0` if unlimited.
     pub max_kills: i32,
+    /// Duration of the match in minutes, `0` if unlimited.
     pub max_time: u32,
 }
 
@@ -32,8 +36,7 @@ impl VanWar {
             team_mode: slice_le_to_i32(&slice[4..8]),
             world_access: slice_le_to_i32(&slice[8..12]),
             max_kills: slice_le_to_i32(&slice[12..16]),
-            // max_time: slice_le_to_i32(&slice[16..20]),
-            max_time: i32::MAX as u32, // TODO: change to correct time set
+            max_time: slice_le_to_i32(&slice[16..20]).max(0) as u32,
         }
     }
 }
@@ -50,7 +53,7 @@ impl Default for VanWar {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Mechosoma {
     pub world: i32,
     pub product_quantity1: i32,
@@ -98,7 +101,7 @@ impl Default for Mechosoma {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Passembloss {
     pub checkpoints_number: i32,
     pub random_escave: i32,
@@ -134,7 +137,7 @@ impl Default for Passembloss {
     }
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize)]
 pub struct Mustodont {
     pub unique_mechos_name: i32,
     pub team_mode: i32,
@@ -0,0 +1,265 @@
+use std::time::Duration;
+
+use ::serde::Serialize;
+
+use crate::player::{Player, Statistics, Status as PlayerStatus};
+use crate::utils::DateTime;
+
+use super::{Config, Game, GameID, GameMode, TeamRecord};
+
+/// Why a match is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchEnd {
+    /// A player has reached `max_kills`.
+    Kills,
+    /// `max_time` is over.
+    Time,
+}
+
+/// Kills and deaths of a player at the end of a match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Score {
+    /// UTF-8 name of the player.
+    pub name: String,
+    pub kills: u8,
+    pub deaths: u8,
+}
+
+/// Result of a finished match.
+#[derive(Debug, Clone)]
+pub struct MatchResult {
+    pub end: MatchEnd,
+    pub duration: Duration,
+    /// Names of players with the most kills, several in case of a draw.
+    pub winners: Vec<String>,
+    pub scores: Vec<Score>,
+}
+
+/// Player in a `MatchRecord`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerRecord {
+    /// UTF-8 name of the player.
+    pub name: String,
+    pub car_index: u8,
+    pub color: u8,
+    pub kills: u8,
+    pub deaths: u8,
+    pub stats: Statistics,
+}
+
+impl PlayerRecord {
+    /// Returns `None` if the player has no body (has not played).
+    pub fn of(player: &Player) -> Option<Self> {
+        let body = player.body.as_ref()?;
+
+        Some(Self {
+            name: player
+                .auth
+                .as_ref()
+                .map(|auth| auth.name_utf8())
+                .unwrap_or_default(),
+            car_index: body.car_index(),
+            color: body.color,
+            kills: body.kills(),
+            deaths: body.deaths(),
+            stats: body.stats.clone(),
+        })
+    }
+}
+
+/// Everything about a game at its end, see `crate::results`.
+#[derive(Debug, Serialize)]
+pub struct MatchRecord<'a> {
+    pub game_id: GameID,
+    pub name: String,
+    /// UTC time of the end.
+    pub time: String,
+    /// Time since the game was created.
+    pub duration_secs: u64,
+    pub config: &'a Config,
+    /// Why the match is over, `None` if all players have left before.
+    pub end: Option<MatchEnd>,
+    pub winners: &'a [String],
+    /// Players who have left the game before its end, then the last players.
+    pub players: Vec<PlayerRecord>,
+    /// Totals of teams, empty if it is not a team game.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub teams: Vec<TeamRecord>,
+}
+
+impl Game {
+    /// Returns the record of the game, `None` if it is not configured.
+    pub fn record(&self) -> Option<MatchRecord<'_>> {
+        let config = self.config.as_ref()?;
+
+        let players = self
+            .left_players
+            .iter()
+            .cloned()
+            .chain(self.players.iter().filter_map(PlayerRecord::of))
+            .collect::<Vec<_>>();
+        let teams = match self.is_team_mode() {
+            true => TeamRecord::of(&players),
+            false => vec![],
+        };
+
+        Some(MatchRecord {
+            game_id: self.id,
+            name: self.name_utf8(),
+            time: DateTime::now().to_string(),
+            duration_secs: self.birth_time.duration().as_secs(),
+            config,
+            end: self.result.as_ref().map(|r| r.end),
+            winners: self.result.as_ref().map_or(&[], |r| &r.winners),
+            players,
+            teams,
+        })
+    }
+
+    /// Returns the reason to finish the match if its limits are reached.
+    /// Only VanWar matches have limits.
+    pub fn match_end(&self) -> Option<MatchEnd> {
+        let Some(GameMode::VanWar(prm)) = self.config.as_ref().map(|c| &c.game_type) else {
+            return None;
+        };
+        if self.result.is_some() {
+            return None;
+        }
+        let clock = self.match_clock.as_ref()?;
+
+        let kills = self
+            .players
+            .iter()
+            .filter_map(|p| p.body.as_ref())
+            .map(|body| body.kills() as i32)
+            .max()
+            .unwrap_or_default();
+
+        if prm.max_kills > 0 && kills >= prm.max_kills {
+            Some(MatchEnd::Kills)
+        } else if prm.max_time > 0 && clock.duration().as_secs() >= u64::from(prm.max_time) * 60 {
+            Some(MatchEnd::Time)
+        } else {
+            None
+        }
+    }
+
+    /// Finishes the match: all players get `FINISHED` status.
+    pub fn finish_match(&mut self, end: MatchEnd) -> &MatchResult {
+        let scores = self
+            .players
+            .iter()
+            .map(|p| Score {
+                name: p.auth.as_ref().map(|a| a.name_utf8()).unwrap_or_default(),
+                kills: p.body.as_ref().map_or(0, |b| b.kills()),
+                deaths: p.body.as_ref().map_or(0, |b| b.deaths()),
+            })
+            .collect::<Vec<_>>();
+
+        let max_kills = scores.iter().map(|s| s.kills).max().unwrap_or_default();
+        let winners = scores
+            .iter()
+            .filter(|s| s.kills == max_kills)
+            .map(|s| s.name.clone())
+            .collect();
+
+        for player in self.players.iter_mut() {
+            player.status = PlayerStatus::FINISHED;
+        }
+
+        self.result.insert(MatchResult {
+            end,
+            duration: self
+                .match_clock
+                .as_ref()
+                .map(|clock| clock.duration())
+                .unwrap_or_default(),
+            winners,
+            scores,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::{Config, Type};
+    use crate::player::Player;
+    use crate::utils::Uptime;
+
+    fn body(kills: u8) -> Vec<u8> {
+        let mut body = vec![0u8; 25];
+        body[0] = kills;
+        body
+    }
+
+    #[test]
+    fn van_war_kills() {
+        let mut game = Game::new(1);
+        let mut config = Config::new(Type::VAN_WAR);
+        if let GameMode::VanWar(ref mut prm) = config.game_type {
+            prm.max_kills = 3;
+        }
+        game.config = Some(config);
+
+        for (client_id, name) in [(11, "a"), (12, "b")] {
+            let mut player = Player::new(client_id);
+            player.set_auth(format!("{}\0", name).as_bytes(), None);
+            player.set_body(Type::VAN_WAR, &body(1)).unwrap();
+            game.attach_player(player);
+        }
+        assert_eq!(None, game.match_end(), "the match is not started");
+
+        game.match_clock = Some(Uptime::new());
+        assert_eq!(None, game.match_end());
+
+        game.players[1].set_body(Type::VAN_WAR, &body(3)).unwrap();
+        assert_eq!(Some(MatchEnd::Kills), game.match_end());
+
+        let result = game.finish_match(MatchEnd::Kills);
+        assert_eq!(vec!["b"], result.winners);
+        assert_eq!(2, result.scores.len());
+        assert!(
+            game.players
+                .iter()
+                .all(|p| p.status == PlayerStatus::FINISHED)
+        );
+        assert_eq!(None, game.match_end(), "the match is already finished");
+    }
+
+    #[test]
+    fn record() {
+        let mut game = Game::new(1);
+        assert!(game.record().is_none(), "the game is not configured");
+
+        game.name = b"Duel\0".to_vec();
+        game.config = Some(Config::new(Type::VAN_WAR));
+        for (client_id, name) in [(11, "a"), (12, "b")] {
+            let mut player = Player::new(client_id);
+            player.set_auth(format!("{}\0", name).as_bytes(), None);
+            player.set_body(Type::VAN_WAR, &body(1)).unwrap();
+            game.attach_player(player);
+        }
+        game.attach_player(Player::new(13));
+        game.detach_player(11);
+
+        let record = game.record().unwrap();
+        assert_eq!("Duel", record.name);
+        assert_eq!(None, record.end);
+        let names = record
+            .players
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["a", "b"], names, "players without body are skipped");
+    }
+
+    #[test]
+    fn other_modes_have_no_limits() {
+        let mut game = Game::new(1);
+        game.config = Some(Config::new(Type::MECHOSOMA));
+        game.match_clock = Some(Uptime::new());
+        assert_eq!(None, game.match_end());
+    }
+}
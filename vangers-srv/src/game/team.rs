@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use ::serde::Serialize;
+
+use crate::client::ClientID;
+use crate::player::Player;
+
+use super::{Game, GameMode, PlayerRecord};
+
+/// Team in a `MatchRecord`: totals of the players of the same color.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TeamRecord {
+    pub color: u8,
+    /// UTF-8 names of the players.
+    pub players: Vec<String>,
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+impl TeamRecord {
+    /// Groups `players` by their colors.
+    pub fn of(players: &[PlayerRecord]) -> Vec<Self> {
+        let mut teams = BTreeMap::<u8, Self>::new();
+        for player in players {
+            let team = teams.entry(player.color).or_insert_with(|| Self {
+                color: player.color,
+                players: vec![],
+                kills: 0,
+                deaths: 0,
+            });
+            team.players.push(player.name.clone());
+            team.kills += u32::from(player.kills);
+            team.deaths += u32::from(player.deaths);
+        }
+        teams.into_values().collect()
+    }
+}
+
+impl Game {
+    /// Returns `true` if the configured mode has `team_mode` enabled:
+    /// players of the same color play as a team.
+    pub fn is_team_mode(&self) -> bool {
+        match self.config.as_ref().map(|c| &c.game_type) {
+            Some(GameMode::VanWar(prm)) => prm.team_mode != 0,
+            Some(GameMode::Mechosoma(prm)) => prm.team_mode != 0,
+            Some(GameMode::Mustodont(prm)) => prm.team_mode != 0,
+            _ => false,
+        }
+    }
+
+    /// Returns the team (the color) of the player `client_id`,
+    /// `None` if it is not a team game or the player has no body yet.
+    pub fn team_of(&self, client_id: ClientID) -> Option<u8> {
+        if !self.is_team_mode() {
+            return None;
+        }
+
+        self.players
+            .iter()
+            .find(|p| p.client_id == client_id)
+            .and_then(|p| p.body.as_ref())
+            .map(|body| body.color)
+    }
+
+    /// Returns other players of the team of `client_id`, see `Game::team_of`.
+    pub fn teammates(&self, client_id: ClientID) -> Option<Vec<ClientID>> {
+        let team = self.team_of(client_id)?;
+
+        let teammates = self
+            .players
+            .iter()
+            .filter(|p| p.client_id != client_id)
+            .filter(|p| p.body.as_ref().is_some_and(|body| body.color == team))
+            .map(|p| p.client_id)
+            .collect();
+
+        Some(teammates)
+    }
+
+    /// Returns players grouped by teams, players without a body are
+    /// under `None`. Empty if it is not a team game.
+    pub fn teams(&self) -> BTreeMap<Option<u8>, Vec<&Player>> {
+        let mut teams = BTreeMap::<_, Vec<_>>::new();
+        if !self.is_team_mode() {
+            return teams;
+        }
+
+        for player in &self.players {
+            let team = player.body.as_ref().map(|body| body.color);
+            teams.entry(team).or_default().push(player);
+        }
+        teams
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::{Config, Type};
+
+    fn body(kills: u8, color: u8) -> Vec<u8> {
+        let mut body = vec![0u8; 25];
+        body[0] = kills;
+        body[2] = color;
+        body
+    }
+
+    fn create_game(team_mode: i32) -> Game {
+        let mut game = Game::new(1);
+        let mut config = Config::new(Type::VAN_WAR);
+        if let GameMode::VanWar(ref mut prm) = config.game_type {
+            prm.team_mode = team_mode;
+        }
+        game.config = Some(config);
+
+        for (client_id, name, color) in [(11, "a", 1), (12, "b", 2), (13, "c", 1)] {
+            let mut player = Player::new(client_id);
+            player.set_auth(format!("{}\0", name).as_bytes(), None);
+            player
+                .set_body(Type::VAN_WAR, &body(client_id as u8 - 10, color))
+                .unwrap();
+            game.attach_player(player);
+        }
+        game.attach_player(Player::new(14));
+        game
+    }
+
+    #[test]
+    fn teams() {
+        let game = create_game(1);
+        assert!(game.is_team_mode());
+        assert_eq!(Some(1), game.team_of(11));
+        assert_eq!(None, game.team_of(14), "no body");
+        assert_eq!(Some(vec![13]), game.teammates(11));
+        assert_eq!(Some(vec![]), game.teammates(12));
+        assert_eq!(None, game.teammates(14));
+
+        let teams = game.teams();
+        assert_eq!(
+            vec![None, Some(1), Some(2)],
+            teams.keys().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(2, teams[&Some(1)].len());
+    }
+
+    #[test]
+    fn no_teams() {
+        let game = create_game(0);
+        assert!(!game.is_team_mode());
+        assert_eq!(None, game.team_of(11));
+        assert_eq!(None, game.teammates(11));
+        assert!(game.teams().is_empty());
+    }
+
+    #[test]
+    fn team_records() {
+        let game = create_game(1);
+        let record = game.record().unwrap();
+        assert_eq!(
+            vec![
+                TeamRecord {
+                    color: 1,
+                    players: vec!["a".to_string(), "c".to_string()],
+                    kills: 4,
+                    deaths: 0,
+                },
+                TeamRecord {
+                    color: 2,
+                    players: vec!["b".to_string()],
+                    kills: 2,
+                    deaths: 0,
+                },
+            ],
+            record.teams
+        );
+
+        let game = create_game(0);
+        assert!(game.record().unwrap().teams.is_empty());
+    }
+}
@@ -1,6 +1,12 @@
 use std::collections::HashMap;
 
-use crate::vanject::Vanject;
+use crate::client::ClientID;
+use crate::vanject::{Pos, Vanject};
+
+use super::Game;
+
+/// Width of all worlds in pixels, worlds are cyclic along both axes.
+pub const WORLD_X_SIZE: i32 = 2048;
 
 #[derive(Debug)]
 pub struct World {
@@ -18,6 +24,15 @@ impl World {
         }
     }
 
+    /// Returns the distance between `a` and `b`, the shortest way may cross
+    /// the edges of the world.
+    pub fn distance(&self, a: Pos<i16>, b: Pos<i16>) -> u32 {
+        let dx = cyclic_delta(a.x, b.x, WORLD_X_SIZE);
+        let dy = cyclic_delta(a.y, b.y, i32::from(self.y_size));
+
+        ((dx * dx + dy * dy) as f64).sqrt() as u32
+    }
+
     // pub fn get_player_inventory<'b>(
     //     &'a self,
     //     player: &'b Player,
@@ -34,3 +49,82 @@ impl World {
     //     println!("World: the vanject `{}` has been added to world #{}", vanject_id, self.id);
     // }
 }
+
+/// Returns the distance between `a` and `b` on a cyclic axis of `size`.
+fn cyclic_delta(a: i16, b: i16, size: i32) -> i64 {
+    let delta = i32::from(a) - i32::from(b);
+    if size <= 0 {
+        return i64::from(delta.abs());
+    }
+
+    let delta = delta.rem_euclid(size);
+    i64::from(delta.min(size - delta))
+}
+
+impl Game {
+    /// Returns receivers of the object `object_id` (see `Game::object_receivers`)
+    /// which are within `range` from the edge of the object.
+    pub fn near_receivers(&self, client_id: ClientID, object_id: i32, range: u32) -> Vec<ClientID> {
+        let Some(vanject) = self.vanjects.get(&object_id) else {
+            return vec![];
+        };
+        let Some(world) = self
+            .worlds
+            .iter()
+            .find(|w| i32::from(w.borrow().id) == vanject.get_world())
+        else {
+            return vec![];
+        };
+        let world = world.borrow();
+        let range = range + vanject.radius.max(0) as u32;
+
+        let receivers = self.object_receivers(client_id, object_id);
+        self.players
+            .iter()
+            .filter(|p| receivers.contains(&p.client_id))
+            .filter(|p| world.distance(p.pos, vanject.pos) <= range)
+            .map(|p| p.client_id)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::fixture;
+    use crate::vanject::NID;
+
+    #[test]
+    fn cyclic_distance() {
+        let world = World::new(0, 16384);
+        let pos = |x, y| Pos { x, y };
+
+        assert_eq!(5, world.distance(pos(0, 0), pos(3, 4)));
+        assert_eq!(10, world.distance(pos(2045, 16380), pos(3, 4)));
+        assert_eq!(1024, world.distance(pos(0, 100), pos(1024, 100)));
+        assert_eq!(8192, world.distance(pos(0, 0), pos(0, 8192)));
+    }
+
+    #[test]
+    fn near_receivers() {
+        let mut game = fixture::game(&["a", "b", "c", "d"]);
+        let pos = |x| Pos { x, y: 0 };
+        fixture::place(
+            &mut game,
+            &[
+                (11, pos(0)),
+                (12, pos(100)),
+                (13, pos(1500)),
+                (14, pos(500)),
+            ],
+        );
+        let id = fixture::add_vanject(&mut game, NID::DEVICE);
+        game.vanjects.get_mut(&id).unwrap().radius = 50;
+
+        assert_eq!(vec![12], game.near_receivers(11, id, 100));
+        assert_eq!(vec![12, 14], game.near_receivers(11, id, 480));
+        // 13 is behind the edge of the world
+        assert_eq!(vec![12, 13, 14], game.near_receivers(11, id, 500));
+        assert!(game.near_receivers(11, 0, 100).is_empty(), "unknown object");
+    }
+}
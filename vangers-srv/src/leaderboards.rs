@@ -0,0 +1,256 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+use ::serde::{Deserialize, Serialize};
+
+use crate::game::{Config, GameMode};
+use crate::player::Statistics;
+use crate::utils::{TomlFileError, load_toml, save_toml, unix_now};
+
+/// Best time of a player on a leaderboard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    /// Name as it was on the last improvement.
+    pub name: String,
+    /// Time of the race in seconds.
+    pub time: u32,
+    /// Unix time of the last improvement.
+    pub date: u64,
+}
+
+/// Best times of players on one configuration of a race mode.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Board {
+    /// Entries by lowercase names.
+    entries: BTreeMap<String, Entry>,
+}
+
+impl Board {
+    /// Returns entries ordered from the best time.
+    pub fn ranking(&self) -> Vec<&Entry> {
+        let mut entries = self.entries.values().collect::<Vec<_>>();
+        entries.sort_by_key(|entry| (entry.time, entry.date));
+        entries
+    }
+
+    /// Returns the place (from 1) and the entry of the player `name`.
+    pub fn place_of(&self, name: &str) -> Option<(usize, &Entry)> {
+        let name = name.to_lowercase();
+        self.ranking()
+            .into_iter()
+            .enumerate()
+            .find(|(_, entry)| entry.name.to_lowercase() == name)
+            .map(|(i, entry)| (i + 1, entry))
+    }
+}
+
+/// Race time formatted as `h:mm:ss`.
+pub struct RaceTime(pub u32);
+
+impl Display for RaceTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let time = self.0;
+        write!(f, "{}:{:02}:{:02}", time / 3600, time / 60 % 60, time % 60)
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LeaderboardsFile {
+    /// Boards by `Leaderboards::key`.
+    boards: BTreeMap<String, Board>,
+}
+
+/// Best times of Passembloss and Mechosoma races, per game configuration.
+/// Saved in a TOML file which is rewritten on every improvement.
+#[derive(Debug, Default)]
+pub struct Leaderboards {
+    /// `None` for in-memory leaderboards.
+    path: Option<PathBuf>,
+    file: LeaderboardsFile,
+}
+
+impl Leaderboards {
+    /// Opens the leaderboards at `path`, the file is created on the first change.
+    pub fn open(path: &Path) -> Result<Self, TomlFileError> {
+        Ok(Self {
+            path: Some(path.to_owned()),
+            file: load_toml(path)?,
+        })
+    }
+
+    /// Returns the key of the leaderboard of games with `config`:
+    /// the mode and parameters which affect the race time.
+    /// `None` if the mode has no leaderboard.
+    pub fn key(config: &Config) -> Option<String> {
+        match config.game_type {
+            GameMode::Passembloss(ref prm) => {
+                Some(format!("passembloss/{}cp", prm.checkpoints_number))
+            }
+            GameMode::Mechosoma(ref prm) => Some(format!(
+                "mechosoma/w{}/{}+{}",
+                prm.world, prm.product_quantity1, prm.product_quantity2
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns the race time of a player with `stats` if the race of
+    /// `config` is completed.
+    pub fn race_time(config: &Config, stats: &Statistics) -> Option<u32> {
+        let time = match (&config.game_type, stats) {
+            (GameMode::Passembloss(prm), Statistics::Passembloss(stats))
+                if stats.checkpoint_lighting >= prm.checkpoints_number =>
+            {
+                stats.total_time
+            }
+            (GameMode::Mechosoma(prm), Statistics::Mechosoma(stats))
+                if stats.item_count1 >= prm.product_quantity1
+                    && stats.item_count2 >= prm.product_quantity2 =>
+            {
+                stats.max_transit_time
+            }
+            _ => return None,
+        };
+
+        u32::try_from(time).ok().filter(|&time| time > 0)
+    }
+
+    /// Count of leaderboards.
+    pub fn len(&self) -> usize {
+        self.file.boards.len()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Board> {
+        self.file.boards.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Board)> {
+        self.file.boards.iter()
+    }
+
+    /// Records `time` of the player `name` on the leaderboard `key`.
+    /// Returns the place of the player if it is the personal best.
+    pub fn submit(
+        &mut self,
+        key: &str,
+        name: &str,
+        time: u32,
+    ) -> Result<Option<usize>, TomlFileError> {
+        let board = self.file.boards.entry(key.to_string()).or_default();
+        match board.entries.get(&name.to_lowercase()) {
+            Some(entry) if entry.time <= time => return Ok(None),
+            _ => {}
+        }
+
+        board.entries.insert(
+            name.to_lowercase(),
+            Entry {
+                name: name.to_string(),
+                time,
+                date: unix_now(),
+            },
+        );
+        let place = board.place_of(name).map(|(place, _)| place);
+
+        self.save()?;
+        Ok(place)
+    }
+
+    fn save(&self) -> Result<(), TomlFileError> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+
+        save_toml(path, &self.file)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::Type;
+
+    fn passembloss(checkpoints: i32, total_time: i32, lighting: i32) -> (Config, Statistics) {
+        let mut config = Config::new(Type::PASSEMBLOSS);
+        if let GameMode::Passembloss(ref mut prm) = config.game_type {
+            prm.checkpoints_number = checkpoints;
+        }
+        let stats = [total_time, lighting, 0, 0]
+            .iter()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        (config, Statistics::from_slice(Type::PASSEMBLOSS, &stats))
+    }
+
+    #[test]
+    fn race_time() {
+        let (config, stats) = passembloss(5, 125, 5);
+        assert_eq!(
+            Some("passembloss/5cp".to_string()),
+            Leaderboards::key(&config)
+        );
+        assert_eq!(Some(125), Leaderboards::race_time(&config, &stats));
+
+        let (config, stats) = passembloss(5, 125, 4);
+        assert_eq!(
+            None,
+            Leaderboards::race_time(&config, &stats),
+            "not finished"
+        );
+
+        let (config, stats) = passembloss(5, 0, 5);
+        assert_eq!(None, Leaderboards::race_time(&config, &stats), "no time");
+
+        let config = Config::new(Type::VAN_WAR);
+        assert_eq!(None, Leaderboards::key(&config));
+        assert_eq!(None, Leaderboards::race_time(&config, &stats));
+
+        assert_eq!("1:02:05", RaceTime(3725).to_string());
+    }
+
+    #[test]
+    fn submit() {
+        let mut boards = Leaderboards::default();
+
+        assert_eq!(Some(1), boards.submit("k", "Fenex", 100).unwrap());
+        assert_eq!(Some(1), boards.submit("k", "Alex", 90).unwrap());
+        assert_eq!(
+            None,
+            boards.submit("k", "fenex", 100).unwrap(),
+            "not better"
+        );
+        assert_eq!(Some(2), boards.submit("k", "FENEX", 95).unwrap());
+        assert_eq!(Some(1), boards.submit("other", "Fenex", 200).unwrap());
+
+        let board = boards.get("k").unwrap();
+        let ranking = board.ranking();
+        assert_eq!(2, ranking.len());
+        assert_eq!(("Alex", 90), (ranking[0].name.as_str(), ranking[0].time));
+        assert_eq!(("FENEX", 95), (ranking[1].name.as_str(), ranking[1].time));
+        assert_eq!(Some(2), board.place_of("fenex").map(|(place, _)| place));
+        assert_eq!(2, boards.len());
+    }
+
+    #[test]
+    fn persistence() {
+        let path = std::env::temp_dir().join(format!(
+            "vangers-srv-leaderboards-{}.toml",
+            std::process::id()
+        ));
+        std::fs::remove_file(&path).ok();
+
+        let mut boards = Leaderboards::open(&path).unwrap();
+        assert_eq!(0, boards.len());
+        boards.submit("passembloss/5cp", "Fenex", 100).unwrap();
+
+        let boards = Leaderboards::open(&path).unwrap();
+        assert_eq!(1, boards.len());
+        assert_eq!(
+            100,
+            boards.get("passembloss/5cp").unwrap().ranking()[0].time
+        );
+
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -1,57 +1,49 @@
 extern crate num_traits;
 
 use ::clap::Parser;
-use ::tracing_subscriber::EnvFilter;
+use ::tracing_subscriber::prelude::*;
+use ::tracing_subscriber::{fmt, reload};
 
+mod accounts;
+mod chat_log;
 mod client;
+mod config;
 mod game;
+mod leaderboards;
 mod player;
 mod protocol;
+mod results;
 mod server;
 mod shell;
 mod utils;
 mod vanject;
 
+use crate::accounts::AccountStore;
+use crate::chat_log::ChatLog;
+use crate::config::{Args, Reloader, ServerConfig};
+use crate::leaderboards::Leaderboards;
+use crate::results::Results;
 use crate::server::Server;
 // use crate::shell::*;
 
-#[derive(Parser, Debug, Default)]
-#[clap(name = "Vangers Server", version, author)]
-struct ServerConfig {
-    #[clap(
-        short,
-        long,
-        default_value = "2197",
-        env = "VANGERS_PORT",
-        help = "Server port to listening incoming in-game player connections"
-    )]
-    pub port: u16,
-    #[clap(
-        long,
-        env = "VANGERS_SUPRESS_LOG_SERVER_TIME",
-        help = "Supress log messages for all SERVER_TIME_* events"
-    )]
-    pub supress_log_server_time: bool,
-    #[clap(
-        long,
-        env = "VANGERS_SUPRESS_LOG_GAMES_LIST_QUERY",
-        help = "Supress log messages for all GAMES_LIST_QUERY events"
-    )]
-    pub supress_log_games_list_query: bool,
-    // #[clap(short, long, help = "Accept incoming connections from localhost only")]
-    // localhost: bool,
-
-    // #[clap(short, long, help = "Enable interactive shell")]
-    // shell: bool,
-}
-
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    ::tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
+    let args = Args::parse();
+    let conf = match ServerConfig::load(&args) {
+        Ok(conf) => conf,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let (log_filter, log_filter_handle) = reload::Layer::new(conf.log_filter());
+    ::tracing_subscriber::registry()
+        .with(log_filter)
+        .with(fmt::layer())
         .init();
 
-    let conf: ServerConfig = ServerConfig::parse();
+    println!("Effective configuration:\n{}", conf.to_toml());
 
     // let shell = ShellCmd::parse_from(vec!["", "tdest"]);
 
@@ -74,10 +66,68 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // println!("is localhost only: {:?}", opts.localhost);
 
+    let accounts = match conf.accounts.path {
+        Some(ref path) => match AccountStore::open(path) {
+            Ok(accounts) => Some(accounts),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let chat_log = match conf.chat_log.dir {
+        Some(ref dir) => match ChatLog::open(dir) {
+            Ok(chat_log) => Some(chat_log),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let results = match conf.results.dir {
+        Some(ref dir) => match Results::open(dir) {
+            Ok(results) => Some(results),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let leaderboards = match conf.leaderboards.path {
+        Some(ref path) => match Leaderboards::open(path) {
+            Ok(leaderboards) => Some(leaderboards),
+            Err(err) => {
+                eprintln!("{}", err);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let shell = args.shell;
     let mut srv = Server::new(conf);
-    // if opts.shell {
-    // srv.enable_shell();
-    // }
+    if let Some(accounts) = accounts {
+        srv.set_accounts(accounts);
+    }
+    if let Some(chat_log) = chat_log {
+        srv.set_chat_log(chat_log);
+    }
+    if let Some(results) = results {
+        srv.set_results(results);
+    }
+    if let Some(leaderboards) = leaderboards {
+        srv.set_leaderboards(leaderboards);
+    }
+    srv.set_reloader(Reloader::new(args, log_filter_handle));
+    if shell {
+        srv.enable_shell();
+    }
     srv.start().await?;
 
     Ok(())
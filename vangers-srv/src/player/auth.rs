@@ -1,27 +1,115 @@
-use std::collections::hash_map::DefaultHasher;
 use std::ffi::CString;
-use std::{
-    fmt::Debug,
-    hash::{Hash, Hasher},
-};
+use std::fmt::Debug;
+use std::str::FromStr;
+
+use ::argon2::password_hash::{self, PasswordHasher, PasswordVerifier, SaltString};
+use ::argon2::{Algorithm, Argon2, Params, Version};
+use ::serde::{Deserialize, Serialize};
 
 use crate::utils::convert_cp866_to_utf8;
 
-fn get_hash_pwd(pwd: &[u8]) -> u64 {
-    // TODO: use more secure algorithm to hashing passwords
-    let mut hasher = DefaultHasher::new();
-    pwd.hash(&mut hasher);
-    hasher.finish()
+/// Salted hash of a password in the PHC string format:
+/// `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`.
+///
+/// The string keeps the algorithm, its version and parameters, so hashes
+/// made with other settings are still verified and may be upgraded
+/// (see `PasswordHash::needs_rehash`). Hashing is slow on purpose,
+/// so it should not be done on the event loop.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PasswordHash(String);
+
+impl PasswordHash {
+    fn hasher() -> Argon2<'static> {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+    }
+
+    /// Hashes `pwd` (without null-terminator) with a random salt.
+    pub fn new(pwd: &[u8]) -> Self {
+        let salt = SaltString::encode_b64(&::rand::random::<[u8; 16]>())
+            .expect("16 bytes is a valid salt length");
+
+        let hash = Self::hasher()
+            .hash_password(pwd, &salt)
+            .expect("default argon2 parameters are valid");
+
+        Self(hash.to_string())
+    }
+
+    /// Returns `true` if `pwd` (without null-terminator) matches the hash.
+    /// The comparison is done in constant time.
+    pub fn verify(&self, pwd: &[u8]) -> bool {
+        match password_hash::PasswordHash::new(&self.0) {
+            Ok(hash) => Self::hasher().verify_password(pwd, &hash).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns `true` if the hash was made by other algorithm or parameters
+    /// than `PasswordHash::new` uses now.
+    pub fn needs_rehash(&self) -> bool {
+        let Ok(hash) = password_hash::PasswordHash::new(&self.0) else {
+            return true;
+        };
+
+        let current = Self::hasher();
+        let current = current.params();
+        hash.algorithm != Algorithm::Argon2id.ident()
+            || hash.version != Some(Version::V0x13.into())
+            || Params::try_from(&hash).map_or(true, |params| {
+                (params.m_cost(), params.t_cost(), params.p_cost())
+                    != (current.m_cost(), current.t_cost(), current.p_cost())
+            })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for PasswordHash {
+    type Err = password_hash::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        password_hash::PasswordHash::new(s)?;
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl TryFrom<String> for PasswordHash {
+    type Error = password_hash::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<PasswordHash> for String {
+    fn from(hash: PasswordHash) -> Self {
+        hash.0
+    }
+}
+
+impl Debug for PasswordHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PasswordHash(..)")
+    }
+}
+
+/// Returns random name like `Player-01234`.
+pub fn guest_name() -> CString {
+    CString::new(format!("Player-{:05}", rand::random::<u16>())).unwrap()
 }
 
 pub struct Auth {
     name: Vec<u8>,
-    #[allow(dead_code)]
-    pwd: Option<u64>,
+    pwd: Option<PasswordHash>,
 }
 
 impl Auth {
-    pub fn new(name: &[u8], pwd: &[u8]) -> Self {
+    /// `pwd` is the hash of a non-empty password, it is made only if
+    /// the player may need it (see `SET_GAME_DATA` of private games).
+    pub fn new(name: &[u8], pwd: Option<PasswordHash>) -> Self {
         let name = match name.last() {
             Some(0) => name.to_owned(),
             Some(_) => {
@@ -29,16 +117,7 @@ impl Auth {
                 name.push(0);
                 name
             }
-            None => CString::new(format!("Player-{:05}", rand::random::<u16>()))
-                .unwrap()
-                .to_bytes_with_nul()
-                .to_owned(),
-        };
-
-        let pwd = if !pwd.is_empty() {
-            Some(get_hash_pwd(pwd))
-        } else {
-            None
+            None => guest_name().into_bytes_with_nul(),
         };
 
         Self { name, pwd }
@@ -48,6 +127,12 @@ impl Auth {
         &self.name
     }
 
+    /// Returns hash of the password, `None` if the password is empty
+    /// or it was not hashed.
+    pub fn pwd(&self) -> Option<&PasswordHash> {
+        self.pwd.as_ref()
+    }
+
     pub fn name_utf8(&self) -> String {
         convert_cp866_to_utf8(&self.name[..self.name.len() - 1]).unwrap_or("unknown".to_string())
     }
@@ -61,30 +146,70 @@ impl Debug for Auth {
 
 #[cfg(test)]
 mod test {
-    use super::Auth;
+    use super::*;
 
     #[test]
     fn test_new_with_empty_name() {
-        let auth = Auth::new(&[], &[]);
+        let auth = Auth::new(&[], None);
         assert_eq!(b"Player-", &auth.name()[..7]);
         assert_eq!(Some(&0), auth.name().last());
     }
 
     #[test]
     fn test_new_without_nullterminated_name() {
-        let auth = Auth::new(b"test-auth", &[]);
-        assert_eq!(b"test-auth\0", &auth.name()[..]);
+        let auth = Auth::new(b"test-auth", None);
+        assert_eq!(b"test-auth\0", auth.name());
     }
 
     #[test]
     fn test_new_with_nullterminated_name() {
-        let auth = Auth::new(b"test-auth\0", &[]);
-        assert_eq!(b"test-auth\0", &auth.name()[..],);
+        let auth = Auth::new(b"test-auth\0", None);
+        assert_eq!(b"test-auth\0", auth.name(),);
     }
 
     #[test]
     fn test_name_utf8() {
-        let auth = Auth::new(b"login\0", b"pwd\0");
+        let auth = Auth::new(b"login\0", None);
         assert_eq!("login", auth.name_utf8());
     }
+
+    #[test]
+    fn test_pwd() {
+        assert!(Auth::new(b"login\0", None).pwd().is_none());
+
+        let auth = Auth::new(b"login\0", Some(PasswordHash::new(b"pwd")));
+        assert!(auth.pwd().unwrap().verify(b"pwd"));
+        assert!(!auth.pwd().unwrap().verify(b"pw"));
+    }
+
+    #[test]
+    fn test_password_hash() {
+        let hash = PasswordHash::new(b"pwd");
+        assert!(hash.as_str().starts_with("$argon2id$v=19$"));
+        assert!(!hash.needs_rehash());
+        assert_ne!(hash, PasswordHash::new(b"pwd"), "hash is salted");
+
+        let parsed: PasswordHash = hash.as_str().parse().unwrap();
+        assert!(parsed.verify(b"pwd"));
+        assert!(!parsed.verify(b"pwd\0"));
+
+        assert!("not a hash".parse::<PasswordHash>().is_err());
+        assert!("0123456789abcdef".parse::<PasswordHash>().is_err());
+        assert_eq!("PasswordHash(..)", format!("{:?}", hash));
+    }
+
+    #[test]
+    fn test_password_hash_other_params() {
+        let weak = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(8, 1, 1, None).unwrap(),
+        );
+        let salt = SaltString::encode_b64(b"saltsalt").unwrap();
+        let hash = weak.hash_password(b"pwd", &salt).unwrap().to_string();
+
+        let hash: PasswordHash = hash.parse().unwrap();
+        assert!(hash.verify(b"pwd"), "parameters are taken from the hash");
+        assert!(hash.needs_rehash());
+    }
 }
@@ -1,3 +1,6 @@
+use ::serde::Serialize;
+
+use crate::game::Type as GameType;
 use crate::protocol::{NetTransportReceive, NetTransportSend};
 use crate::utils::*;
 
@@ -6,15 +9,52 @@ use super::stats::{
     PassemblossStatistic as Passembloss, VanWarStatistics as VanWar,
 };
 
-#[allow(dead_code)]
-#[derive(Debug)]
+/// Per-mode statistics of a player, the tail of `Body`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Statistics {
-    UNDEFINED,
     VanWar(VanWar),
     Mechosoma(Mechosoma),
     Passembloss(Passembloss),
     Huntage,
     Mustodont(Mustodont),
+    /// Statistics of an unconfigured game or of unexpected size, kept as is.
+    Raw(Vec<u8>),
+}
+
+impl Statistics {
+    /// Decodes `slice` as statistics of `gametype`.
+    pub fn from_slice(gametype: GameType, slice: &[u8]) -> Self {
+        let stats = match gametype {
+            GameType::VAN_WAR => VanWar::from_slice(slice).map(Self::VanWar),
+            GameType::MECHOSOMA => Mechosoma::from_slice(slice).map(Self::Mechosoma),
+            GameType::PASSEMBLOSS => Passembloss::from_slice(slice).map(Self::Passembloss),
+            GameType::HUNTAGE if slice.is_empty() => Some(Self::Huntage),
+            GameType::MUSTODONT => Mustodont::from_slice(slice).map(Self::Mustodont),
+            _ => None,
+        };
+
+        stats.unwrap_or_else(|| Self::Raw(slice.to_vec()))
+    }
+}
+
+impl Default for Statistics {
+    fn default() -> Self {
+        Self::Raw(vec![])
+    }
+}
+
+impl NetTransportSend for Statistics {
+    fn to_vangers_byte(&self) -> Vec<u8> {
+        match self {
+            Self::VanWar(s) => s.to_vangers_byte(),
+            Self::Mechosoma(s) => s.to_vangers_byte(),
+            Self::Passembloss(s) => s.to_vangers_byte(),
+            Self::Mustodont(s) => s.to_vangers_byte(),
+            Self::Huntage => vec![],
+            Self::Raw(bytes) => bytes.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -30,41 +70,31 @@ pub struct Body {
     data2: i16,
     birth_time: u32,
     net_id: i32,
-    // TODO: change `stats` to one of:
-    //  - convert to <dyn PlayerStatistics>
-    //  - use enum (see body_alt.rs)
-    //  - something else?
-    stats: Vec<u8>,
+    pub stats: Statistics,
 }
 
 impl Body {
+    pub fn kills(&self) -> u8 {
+        self.kills
+    }
+
+    pub fn deaths(&self) -> u8 {
+        self.deaths
+    }
+
+    pub fn car_index(&self) -> u8 {
+        self.car_index
+    }
+
     /// Returns summary size of each field exclude `stats`.
     /// Not use `std::mem::size_of()` because of memory align.
     /// TODO: convert to derive proc_macro.
     fn get_base_struct_size() -> usize {
         1 + 1 + 1 + 1 + 4 + 4 + 1 + 2 + 2 + 4 + 4
     }
-}
-
-impl NetTransportSend for Body {
-    fn to_vangers_byte(&self) -> Vec<u8> {
-        [self.kills, self.deaths, self.color, self.world]
-            .iter()
-            .chain(&self.beebos.to_le_bytes())
-            .chain(&self.rating.to_le_bytes())
-            .chain(&[self.car_index])
-            .chain(&self.data1.to_le_bytes())
-            .chain(&self.data2.to_le_bytes())
-            .chain(&self.birth_time.to_le_bytes())
-            .chain(&self.net_id.to_le_bytes())
-            .chain(&self.stats)
-            .copied()
-            .collect::<Vec<_>>()
-    }
-}
 
-impl NetTransportReceive for Body {
-    fn from_slice(slice: &[u8]) -> Option<Self> {
+    /// Decodes `slice`, the statistics are decoded according to `gametype`.
+    pub fn from_slice(gametype: GameType, slice: &[u8]) -> Option<Self> {
         let base_count_bytes = Self::get_base_struct_size();
         if slice.len() < base_count_bytes {
             return None;
@@ -86,7 +116,7 @@ impl NetTransportReceive for Body {
         let birth_time = slice_le_to_u32(&slice[17..21]);
         let net_id = slice_le_to_i32(&slice[21..25]);
 
-        let stats = slice[25..].to_vec();
+        let stats = Statistics::from_slice(gametype, &slice[25..]);
 
         Some(Self {
             kills,
@@ -105,6 +135,23 @@ impl NetTransportReceive for Body {
     }
 }
 
+impl NetTransportSend for Body {
+    fn to_vangers_byte(&self) -> Vec<u8> {
+        [self.kills, self.deaths, self.color, self.world]
+            .iter()
+            .chain(&self.beebos.to_le_bytes())
+            .chain(&self.rating.to_le_bytes())
+            .chain(&[self.car_index])
+            .chain(&self.data1.to_le_bytes())
+            .chain(&self.data2.to_le_bytes())
+            .chain(&self.birth_time.to_le_bytes())
+            .chain(&self.net_id.to_le_bytes())
+            .chain(&self.stats.to_vangers_byte())
+            .copied()
+            .collect::<Vec<_>>()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -123,23 +170,7 @@ mod test {
             .collect::<Vec<_>>()
     }
 
-    #[test]
-    fn body_from_slice_vanwar() {
-        let mut data_base = get_body_base_slice();
-        let stats = std::iter::empty()
-            .chain(&20i32.to_le_bytes()) //max_live_time
-            .chain(&21i32.to_le_bytes()) //min_live_time
-            .chain(&22i32.to_le_bytes()) //kill_freq
-            .chain(&23i32.to_le_bytes()) //death_freaq
-            .map(|&b| b)
-            .collect::<Vec<_>>();
-
-        data_base.append(&mut stats.clone());
-
-        let body = Body::from_slice(&data_base[..]);
-        assert!(body.is_some());
-        let body = body.unwrap();
-
+    fn assert_base(body: &Body) {
         assert_eq!(1, body.kills);
         assert_eq!(2, body.deaths);
         assert_eq!(3, body.color);
@@ -151,14 +182,38 @@ mod test {
         assert_eq!(9, body.data2);
         assert_eq!(10, body.birth_time);
         assert_eq!(11, body.net_id);
+    }
+
+    #[test]
+    fn body_from_slice_vanwar() {
+        let data = get_body_base_slice()
+            .iter()
+            .chain(&20i32.to_le_bytes()) //max_live_time
+            .chain(&21i32.to_le_bytes()) //min_live_time
+            .chain(&22i32.to_le_bytes()) //kill_freq
+            .chain(&23i32.to_le_bytes()) //death_freaq
+            .map(|&b| b)
+            .collect::<Vec<_>>();
 
-        assert_eq!(&stats, &body.stats);
+        let body = Body::from_slice(GameType::VAN_WAR, &data).unwrap();
+        assert_base(&body);
+        assert_eq!(data, body.to_vangers_byte());
+
+        match body.stats {
+            Statistics::VanWar(s) => {
+                assert_eq!(20, s.max_live_time);
+                assert_eq!(21, s.min_live_time);
+                assert_eq!(22, s.kill_freq);
+                assert_eq!(23, s.death_freaq);
+            }
+            s => panic!("missmatch stats, expected: `VanWar`, actual: {:?}", s),
+        }
     }
 
     #[test]
     fn body_from_slice_mechosoma() {
-        let mut data_base = get_body_base_slice();
-        let stats = std::iter::empty()
+        let data = get_body_base_slice()
+            .iter()
             .chain(&20i32.to_le_bytes()) //item_count1
             .chain(&21i32.to_le_bytes()) //item_count2
             .chain(&22i32.to_le_bytes()) //max_transit_time
@@ -168,31 +223,27 @@ mod test {
             .map(|&b| b)
             .collect::<Vec<_>>();
 
-        data_base.append(&mut stats.clone());
-
-        let body = Body::from_slice(&data_base[..]);
-        assert!(body.is_some());
-        let body = body.unwrap();
-
-        assert_eq!(1, body.kills);
-        assert_eq!(2, body.deaths);
-        assert_eq!(3, body.color);
-        assert_eq!(4, body.world);
-        assert_eq!(5, body.beebos);
-        assert_eq!(6.0, body.rating);
-        assert_eq!(7, body.car_index);
-        assert_eq!(8, body.data1);
-        assert_eq!(9, body.data2);
-        assert_eq!(10, body.birth_time);
-        assert_eq!(11, body.net_id);
-
-        assert_eq!(&stats, &body.stats);
+        let body = Body::from_slice(GameType::MECHOSOMA, &data).unwrap();
+        assert_base(&body);
+        assert_eq!(data, body.to_vangers_byte());
+
+        match body.stats {
+            Statistics::Mechosoma(s) => {
+                assert_eq!(20, s.item_count1);
+                assert_eq!(21, s.item_count2);
+                assert_eq!(22, s.max_transit_time);
+                assert_eq!(23, s.min_transit_time);
+                assert_eq!(24, s.sneak_count);
+                assert_eq!(25, s.lost_count);
+            }
+            s => panic!("missmatch stats, expected: `Mechosoma`, actual: {:?}", s),
+        }
     }
 
     #[test]
     fn body_from_slice_passembloss() {
-        let mut data_base = get_body_base_slice();
-        let stats = std::iter::empty()
+        let data = get_body_base_slice()
+            .iter()
             .chain(&20i32.to_le_bytes()) //total_time
             .chain(&21i32.to_le_bytes()) //checkpoint_lighting
             .chain(&22i32.to_le_bytes()) //min_time
@@ -200,31 +251,25 @@ mod test {
             .map(|&b| b)
             .collect::<Vec<_>>();
 
-        data_base.append(&mut stats.clone());
-
-        let body = Body::from_slice(&data_base[..]);
-        assert!(body.is_some());
-        let body = body.unwrap();
-
-        assert_eq!(1, body.kills);
-        assert_eq!(2, body.deaths);
-        assert_eq!(3, body.color);
-        assert_eq!(4, body.world);
-        assert_eq!(5, body.beebos);
-        assert_eq!(6.0, body.rating);
-        assert_eq!(7, body.car_index);
-        assert_eq!(8, body.data1);
-        assert_eq!(9, body.data2);
-        assert_eq!(10, body.birth_time);
-        assert_eq!(11, body.net_id);
-
-        assert_eq!(&stats, &body.stats);
+        let body = Body::from_slice(GameType::PASSEMBLOSS, &data).unwrap();
+        assert_base(&body);
+        assert_eq!(data, body.to_vangers_byte());
+
+        match body.stats {
+            Statistics::Passembloss(s) => {
+                assert_eq!(20, s.total_time);
+                assert_eq!(21, s.checkpoint_lighting);
+                assert_eq!(22, s.min_time);
+                assert_eq!(23, s.max_time);
+            }
+            s => panic!("missmatch stats, expected: `Passembloss`, actual: {:?}", s),
+        }
     }
 
     #[test]
     fn body_from_slice_mustodont() {
-        let mut data_base = get_body_base_slice();
-        let stats = std::iter::empty()
+        let data = get_body_base_slice()
+            .iter()
             .chain(&20i32.to_le_bytes()) //part_time1
             .chain(&21i32.to_le_bytes()) //part_time2
             .chain(&22i32.to_le_bytes()) //body_time
@@ -232,34 +277,46 @@ mod test {
             .map(|&b| b)
             .collect::<Vec<_>>();
 
-        data_base.append(&mut stats.clone());
-
-        let body = Body::from_slice(&data_base[..]);
-        assert!(body.is_some());
-        let body = body.unwrap();
-
-        assert_eq!(1, body.kills);
-        assert_eq!(2, body.deaths);
-        assert_eq!(3, body.color);
-        assert_eq!(4, body.world);
-        assert_eq!(5, body.beebos);
-        assert_eq!(6.0, body.rating);
-        assert_eq!(7, body.car_index);
-        assert_eq!(8, body.data1);
-        assert_eq!(9, body.data2);
-        assert_eq!(10, body.birth_time);
-        assert_eq!(11, body.net_id);
+        let body = Body::from_slice(GameType::MUSTODONT, &data).unwrap();
+        assert_base(&body);
+        assert_eq!(data, body.to_vangers_byte());
+
+        match body.stats {
+            Statistics::Mustodont(s) => {
+                assert_eq!(20, s.part_time1);
+                assert_eq!(21, s.part_time2);
+                assert_eq!(22, s.body_time);
+                assert_eq!(23, s.make_time);
+            }
+            s => panic!("missmatch stats, expected: `Mustodont`, actual: {:?}", s),
+        }
+    }
 
-        assert_eq!(&stats, &body.stats);
+    #[test]
+    fn body_from_slice_raw_stats() {
+        let base = get_body_base_slice();
+
+        let body = Body::from_slice(GameType::HUNTAGE, &base).unwrap();
+        assert!(matches!(body.stats, Statistics::Huntage));
+        assert_eq!(base, body.to_vangers_byte());
+
+        // unexpected size of the statistics or unknown game type
+        let data = [&base[..], &[1, 2, 3]].concat();
+        for gametype in [GameType::VAN_WAR, GameType::UNCONFIGURED] {
+            let body = Body::from_slice(gametype, &data).unwrap();
+            assert_base(&body);
+            assert!(matches!(body.stats, Statistics::Raw(ref s) if s == &[1, 2, 3]));
+            assert_eq!(data, body.to_vangers_byte());
+        }
     }
 
     #[test]
     fn body_from_slice_incorrect_size() {
         fn assert(data: &[u8]) {
-            assert!(Body::from_slice(&data[..]).is_none());
-            assert!(Body::from_slice(&data[..]).is_none());
-            assert!(Body::from_slice(&data[..]).is_none());
-            assert!(Body::from_slice(&data[..]).is_none());
+            assert!(Body::from_slice(GameType::VAN_WAR, data).is_none());
+            assert!(Body::from_slice(GameType::MECHOSOMA, data).is_none());
+            assert!(Body::from_slice(GameType::PASSEMBLOSS, data).is_none());
+            assert!(Body::from_slice(GameType::MUSTODONT, data).is_none());
         }
 
         assert(&[]);
@@ -1,7 +1,6 @@
 mod auth;
 mod bind;
 mod body;
-// mod body_alt;
 mod player;
 mod stats;
 
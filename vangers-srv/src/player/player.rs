@@ -3,13 +3,14 @@ use std::rc::Rc;
 
 use ::tracing::info;
 
-use crate::game::World;
-use crate::protocol::NetTransportReceive;
-use crate::{client::ClientID, vanject::Pos};
+use crate::client::ClientID;
+use crate::game::{Type as GameType, World};
+use crate::vanject::{Pos, get_world, is_non_global_vanject};
 
 use super::Auth;
 use super::Bind;
 use super::Body;
+use super::PasswordHash;
 
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Status {
@@ -18,6 +19,41 @@ pub enum Status {
     FINISHED = 2,
 }
 
+/// Last accepted position of the vanger of a player, see `Track::jump`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Track {
+    pub pos: Pos<i16>,
+    /// `time` of the object update, milliseconds of the client clock.
+    pub time: i32,
+    pub world: u8,
+    /// Deaths of the player, a new death means a respawn.
+    pub deaths: u8,
+}
+
+impl Track {
+    /// Returns the distance and the allowed distance if the move to `next`
+    /// is faster than `max_speed` pixels per second (plus `tolerance` pixels).
+    /// Moves to another world and respawns are always plausible.
+    pub fn jump(
+        &self,
+        next: &Track,
+        world: &World,
+        max_speed: u32,
+        tolerance: u32,
+    ) -> Option<(u32, u32)> {
+        if self.world != next.world || self.deaths != next.deaths {
+            return None;
+        }
+
+        let millis = next.time.wrapping_sub(self.time).max(0) as u64;
+        let allowed = u64::from(tolerance) + u64::from(max_speed) * millis / 1000;
+        let allowed = u32::try_from(allowed).unwrap_or(u32::MAX);
+        let distance = world.distance(self.pos, next.pos);
+
+        (distance > allowed).then_some((distance, allowed))
+    }
+}
+
 #[derive(Debug)]
 pub struct Player {
     /// The id that bind `Player` & `Client` structs.
@@ -32,6 +68,8 @@ pub struct Player {
     pub world: Option<Rc<RefCell<World>>>,
 
     pub pos: Pos<i16>,
+    /// Movement of the vanger, `None` until its first update.
+    pub track: Option<Track>,
 
     pub status: Status,
 }
@@ -45,6 +83,7 @@ impl Player {
             body: None,
             world: None,
             pos: Pos::default(),
+            track: None,
             status: Status::INITIAL,
         }
     }
@@ -69,6 +108,18 @@ impl Player {
     //     }
     // }
 
+    /// Returns the id of the world the player is in.
+    pub fn world_id(&self) -> Option<u8> {
+        self.world.as_ref().map(|world| world.borrow().id)
+    }
+
+    /// Returns `true` if the client of the player needs the object `object_id`:
+    /// global objects are needed everywhere, others in their world only.
+    pub fn sees_object(&self, object_id: i32) -> bool {
+        !is_non_global_vanject(object_id)
+            || self.world_id().map(i32::from) == Some(get_world(object_id))
+    }
+
     #[allow(dead_code)]
     pub fn bind_reset(&mut self) {
         self.set_bind(0);
@@ -83,7 +134,7 @@ impl Player {
         }
     }
 
-    pub fn set_auth(&mut self, name: &[u8], pwd: &[u8]) {
+    pub fn set_auth(&mut self, name: &[u8], pwd: Option<PasswordHash>) {
         #[inline(always)]
         fn check_for_null_terminate(s: &[u8]) -> bool {
             s.len() > 1 && s[s.len() - 1] == 0
@@ -96,8 +147,9 @@ impl Player {
         self.auth = Some(Auth::new(name, pwd));
     }
 
-    pub fn set_body(&mut self, slice: &[u8]) -> Result<(), &'static str> {
-        self.body = Body::from_slice(slice);
+    /// Sets the body, its statistics are decoded according to `gametype`.
+    pub fn set_body(&mut self, gametype: GameType, slice: &[u8]) -> Result<(), &'static str> {
+        self.body = Body::from_slice(gametype, slice);
 
         match self.body {
             Some(ref body) => {
@@ -113,3 +165,46 @@ impl Player {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn track(x: i16, y: i16, time: i32) -> Track {
+        Track {
+            pos: Pos { x, y },
+            time,
+            world: 0,
+            deaths: 0,
+        }
+    }
+
+    #[test]
+    fn jump() {
+        let world = World::new(0, 16384);
+        let prev = track(100, 100, 1000);
+
+        assert_eq!(None, prev.jump(&track(200, 100, 1100), &world, 1000, 0));
+        assert_eq!(
+            Some((200, 100)),
+            prev.jump(&track(300, 100, 1100), &world, 1000, 0)
+        );
+        assert_eq!(None, prev.jump(&track(300, 100, 1100), &world, 1000, 100));
+        assert_eq!(
+            None,
+            prev.jump(&track(100, 16300, 1100), &world, 1000, 100),
+            "the world is cyclic"
+        );
+
+        let respawn = Track {
+            deaths: 1,
+            ..track(1000, 5000, 1001)
+        };
+        assert_eq!(None, prev.jump(&respawn, &world, 1000, 0));
+        let other_world = Track {
+            world: 1,
+            ..track(1000, 5000, 1001)
+        };
+        assert_eq!(None, prev.jump(&other_world, &world, 1000, 0));
+    }
+}
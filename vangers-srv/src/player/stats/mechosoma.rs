@@ -1,7 +1,7 @@
 use super::*;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MechosomaStatistic {
     pub item_count1: i32,
     pub item_count2: i32,
@@ -8,6 +8,8 @@ pub use mustodont::*;
 pub use passembloss::*;
 pub use vanwar::*;
 
+use ::serde::Serialize;
+
 use crate::protocol::{NetTransport, NetTransportReceive, NetTransportSend};
 use crate::utils::slice_le_to_i32;
 
@@ -1,7 +1,7 @@
 use super::*;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MustodontStatistic {
     pub part_time1: i32,
     pub part_time2: i32,
@@ -1,7 +1,7 @@
 use super::*;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct PassemblossStatistic {
     pub total_time: i32, //hh:mm:ss
     pub checkpoint_lighting: i32,
@@ -1,7 +1,7 @@
 use super::*;
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct VanWarStatistics {
     pub max_live_time: i32, // None
     pub min_live_time: i32, // None
@@ -8,6 +8,13 @@ use ::tracing::warn;
 // pub const AUXILIARY_EVENT: u8 = 0x80;
 // pub const ECHO_EVENT: u8 = 0x20;
 
+/// Max length of `DIRECT_RECEIVING` message including null-terminator.
+pub const LIMIT_MSG_LEN: usize = 140;
+
+/// Sender id of `DIRECT_RECEIVING` messages created by the server itself.
+/// Players are bound to ids in range [1..31], so the id never belongs to a player.
+pub const SYSTEM_SENDER_ID: u8 = 0;
+
 pub trait NetTransportSend {
     fn to_vangers_byte(&self) -> Vec<u8>;
 }
@@ -0,0 +1,99 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::game::MatchRecord;
+use crate::utils::DateTime;
+
+#[derive(Debug, ::thiserror::Error)]
+pub enum ResultsError {
+    #[error("cannot create results directory `{0}`: {1}")]
+    CreateDir(PathBuf, std::io::Error),
+    #[error("cannot serialize match record: {0}")]
+    Serialize(::serde_json::Error),
+    #[error("cannot write results file `{0}`: {1}")]
+    Write(PathBuf, std::io::Error),
+}
+
+/// Match records written to daily files `results-<date>.jsonl` inside of
+/// a directory, one JSON object per line. The files are never removed.
+#[derive(Debug)]
+pub struct Results {
+    dir: PathBuf,
+}
+
+impl Results {
+    pub fn open(dir: &Path) -> Result<Self, ResultsError> {
+        std::fs::create_dir_all(dir).map_err(|err| ResultsError::CreateDir(dir.to_owned(), err))?;
+
+        Ok(Self {
+            dir: dir.to_owned(),
+        })
+    }
+
+    /// Appends `record` to the file of the current day.
+    pub fn write(&self, record: &MatchRecord) -> Result<(), ResultsError> {
+        let mut line = ::serde_json::to_string(record).map_err(ResultsError::Serialize)?;
+        line.push('\n');
+
+        let path = self.path(&DateTime::now().date());
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(line.as_bytes()))
+            .map_err(|err| ResultsError::Write(path, err))
+    }
+
+    fn path(&self, date: &str) -> PathBuf {
+        self.dir.join(format!("results-{}.jsonl", date))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::{Config, Game, Type};
+    use crate::player::Player;
+
+    #[test]
+    fn write_records() {
+        let dir = std::env::temp_dir().join(format!("vangers-srv-results-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+
+        let mut game = Game::new(7);
+        game.name = b"Duel\0".to_vec();
+        game.config = Some(Config::new(Type::PASSEMBLOSS));
+        let mut player = Player::new(11);
+        player.set_auth(b"Fenex\0", None);
+        let body = [&[3u8, 1, 5, 0][..], &[0; 21], &[0; 16]].concat();
+        player.set_body(Type::PASSEMBLOSS, &body).unwrap();
+        game.attach_player(player);
+
+        let results = Results::open(&dir).unwrap();
+        results.write(&game.record().unwrap()).unwrap();
+        results.write(&game.record().unwrap()).unwrap();
+
+        let content = std::fs::read_to_string(results.path(&DateTime::now().date())).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(2, lines.len());
+
+        let record: ::serde_json::Value = ::serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(7, record["game_id"]);
+        assert_eq!("Duel", record["name"]);
+        assert_eq!("passembloss", record["config"]["game_type"]["mode"]);
+        assert_eq!(
+            10,
+            record["config"]["game_type"]["params"]["checkpoints_number"]
+        );
+        assert_eq!("Fenex", record["players"][0]["name"]);
+        assert_eq!(3, record["players"][0]["kills"]);
+        assert_eq!(5, record["players"][0]["color"]);
+        assert_eq!(
+            0,
+            record["players"][0]["stats"]["passembloss"]["total_time"]
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
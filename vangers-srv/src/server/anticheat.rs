@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use ::tracing::warn;
+
+use crate::client::ClientID;
+use crate::player::Track;
+use crate::vanject::Pos;
+
+use super::Server;
+
+/// Cheat attempt detected by the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// `CREATE_OBJECT` with the station of another player.
+    WrongStation { object_id: i32, station: u8 },
+    /// `UPDATE_OBJECT` of an object owned by another player.
+    ForeignUpdate { object_id: i32, owner: u8 },
+    /// `DELETE_OBJECT` of an object owned by another player.
+    ForeignDelete { object_id: i32, owner: u8 },
+    /// The vanger has moved farther than allowed by `movement.max_speed`.
+    Teleport { distance: u32, allowed: u32 },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongStation { object_id, station } => write!(
+                f,
+                "creates object `{}` of the station `{}`",
+                object_id, station
+            ),
+            Self::ForeignUpdate { object_id, owner } => {
+                write!(f, "updates object `{}` of player_id=`{}`", object_id, owner)
+            }
+            Self::ForeignDelete { object_id, owner } => {
+                write!(f, "deletes object `{}` of player_id=`{}`", object_id, owner)
+            }
+            Self::Teleport { distance, allowed } => {
+                write!(
+                    f,
+                    "moves the vanger by {} px, allowed {} px",
+                    distance, allowed
+                )
+            }
+        }
+    }
+}
+
+/// Counts of violations by clients.
+#[derive(Debug, Default)]
+pub struct AntiCheat {
+    counts: HashMap<ClientID, u32>,
+    total: u64,
+}
+
+impl AntiCheat {
+    /// Counts a violation of `client_id`, returns the count of the client.
+    pub fn count(&mut self, client_id: ClientID) -> u32 {
+        self.total += 1;
+        let count = self.counts.entry(client_id).or_default();
+        *count += 1;
+        *count
+    }
+
+    /// Count of violations of `client_id`.
+    pub fn of(&self, client_id: ClientID) -> u32 {
+        self.counts.get(&client_id).copied().unwrap_or_default()
+    }
+
+    /// Count of violations of all clients since the start.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Forgets violations of the disconnected `client_id`.
+    pub fn forget(&mut self, client_id: ClientID) {
+        self.counts.remove(&client_id);
+    }
+}
+
+impl Server {
+    /// Logs and counts the `violation` of `client_id`,
+    /// admins are notified of the first violation of each client.
+    pub(in crate::server) fn report_violation(
+        &mut self,
+        client_id: ClientID,
+        violation: Violation,
+    ) {
+        let count = self.anticheat.count(client_id);
+        let name = self
+            .games
+            .get_player_by_client_id(client_id)
+            .and_then(|(_, p)| p.auth.as_ref())
+            .map(|auth| auth.name_utf8())
+            .unwrap_or_default();
+
+        warn!(
+            "anti-cheat: client_id=`{}` (`{}`) {}, violations: {}",
+            client_id, name, violation, count
+        );
+        if count == 1 {
+            self.notify_admins(&format!("Anti-cheat: `{}` {}", name, violation));
+        }
+    }
+
+    /// Checks the move of the vanger of `client_id` to `pos` at `time`,
+    /// see `Track::jump`. Returns `false` if the update should be dropped.
+    /// The track follows the vanger after a jump anyway, so a jump is reported
+    /// once and the vanger is not stuck at the position before it.
+    pub(in crate::server) fn check_movement(
+        &mut self,
+        client_id: ClientID,
+        pos: Pos<i16>,
+        time: i32,
+    ) -> bool {
+        let conf = self.conf.movement.clone();
+        if !conf.enabled {
+            return true;
+        }
+
+        let Some(player) = self
+            .get_mut_game_by_clientid(client_id)
+            .and_then(|game| game.get_mut_player(client_id))
+        else {
+            return true;
+        };
+        let Some(world) = player.world.clone() else {
+            return true;
+        };
+        let world = world.borrow();
+
+        let next = Track {
+            pos,
+            time,
+            world: world.id,
+            deaths: player.body.as_ref().map_or(0, |body| body.deaths()),
+        };
+        let jump = player
+            .track
+            .and_then(|prev| prev.jump(&next, &world, conf.max_speed, conf.tolerance));
+        player.track = Some(next);
+        drop(world);
+
+        match jump {
+            Some((distance, allowed)) => {
+                self.report_violation(client_id, Violation::Teleport { distance, allowed });
+                !conf.drop_jumps
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn count() {
+        let mut anticheat = AntiCheat::default();
+        assert_eq!(1, anticheat.count(11));
+        assert_eq!(2, anticheat.count(11));
+        assert_eq!(1, anticheat.count(12));
+        assert_eq!(2, anticheat.of(11));
+        assert_eq!(3, anticheat.total());
+
+        anticheat.forget(11);
+        assert_eq!(0, anticheat.of(11));
+        assert_eq!(3, anticheat.total());
+    }
+}
@@ -1,4 +1,4 @@
-use ::tracing::info;
+use ::tracing::{info, warn};
 
 use crate::Server;
 use crate::client::ClientID;
@@ -14,8 +14,6 @@ pub enum AttachToGameError {
     NotExists(u32),
     #[error("required byte `game_id` not found")]
     IdEmpty,
-    #[error("game have no free player slots")]
-    Full(u32),
 }
 
 #[allow(non_camel_case_types)]
@@ -42,19 +40,57 @@ impl OnUpdate_AttachToGame for Server {
             0 => {
                 let gmid = self.get_game_uniq_id();
                 self.games.create(gmid).ok();
+                if let Some(game) = self.games.get_mut_game_by_id(gmid) {
+                    // the actual capacity is chosen by the name of the game, see `SET_GAME_DATA`
+                    game.max_players = self.conf.capacity.default;
+                }
                 gmid as i32
             }
             gmid => gmid,
         };
 
+        let addr = self.addr_of(client_id);
         let game = match self.games.get_mut_game_by_id(gmid as u32) {
             Some(game) => game,
             None => return Err(AttachToGameError::NotExists(gmid as u32).into()),
         };
 
-        let player_id = match game.attach_player(Player::new(client_id)) {
-            None => return Err(AttachToGameError::Full(game.id).into()),
+        // a kicked player may come back under a new name, see `Game::ban`
+        let banned = game.is_banned(None, addr);
+        let attached = match game.closed || banned {
+            true => None,
+            false => game.attach_player(Player::new(client_id)),
+        };
+
+        let player_id = match attached {
             Some(p_id) => p_id,
+            None => {
+                warn!(
+                    "game_id=`{gmid}` is {} ({}/{}), refuse to attach client_id=`{client_id}`",
+                    if banned {
+                        "banning the client"
+                    } else if game.closed {
+                        "closed"
+                    } else {
+                        "full"
+                    },
+                    game.players.len(),
+                    game.max_players
+                );
+                // `player_id=0` tells the client that it was not attached
+                let data = std::iter::empty()
+                    .chain(&game.id.to_le_bytes())
+                    .chain(&(if game.is_configured() { 1u8 } else { 0u8 }).to_le_bytes())
+                    .chain(&(game.birth_time.as_secs_u32() as i32).to_le_bytes())
+                    .chain(&[0u8])
+                    .chain(&[0u8; 32]) //object_ID_offsets
+                    .copied()
+                    .collect();
+                return packet
+                    .create_answer(data)
+                    .map(OnUpdateOk::Response)
+                    .ok_or(OnUpdateError::ResponsePacketTypeNotExist(packet.action));
+            }
         };
 
         info!("attached player_id=`{player_id}` to game_id=`{gmid}`");
@@ -96,9 +132,8 @@ impl OnUpdate_AttachToGame for Server {
 
         let packets = game
             .vanjects
-            .iter()
-            // .filter(|(_, v)| !v.is_non_global())
-            .map(|(_, v)| v.to_vangers_byte())
+            .values()
+            .map(|v| v.to_vangers_byte())
             .map(|v| Packet::new(Action::UPDATE_OBJECT, &v[..]))
             .collect::<Vec<_>>();
 
@@ -121,6 +156,86 @@ impl OnUpdate_AttachToGame for Server {
             .iter()
             .for_each(|packet| self.notify_player(client_id, packet));
 
+        self.send_motd(client_id);
+
         Ok(OnUpdateOk::Complete)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn attach(srv: &mut Server, game_id: u32, client_id: ClientID) -> Option<u8> {
+        let request = Packet::new(Action::ATTACH_TO_GAME, &game_id.to_le_bytes());
+        srv.attach_to_game(&request, client_id).unwrap();
+        srv.games
+            .get_player_by_client_id(client_id)
+            .and_then(|(_, p)| p.bind)
+            .map(|bind| bind.id())
+    }
+
+    #[test]
+    fn refuse_attach_to_full_game() {
+        let mut srv = Server::new(Default::default());
+        srv.conf.capacity.default = 2;
+
+        assert_eq!(Some(1), attach(&mut srv, 0, 1));
+        assert_eq!(2, srv.games[&1].max_players);
+        assert_eq!(Some(2), attach(&mut srv, 1, 2));
+
+        let request = Packet::new(Action::ATTACH_TO_GAME, &1u32.to_le_bytes());
+        match srv.attach_to_game(&request, 3) {
+            Ok(OnUpdateOk::Response(p)) => {
+                assert_eq!(Action::ATTACH_TO_GAME_RESPONSE, p.action);
+                assert_eq!(0, p.data[9], "player_id");
+            }
+            r => panic!("unexpected result: {:?}", r),
+        }
+        assert_eq!(2, srv.games[&1].players.len());
+    }
+
+    #[test]
+    fn refuse_attach_to_closed_game() {
+        let mut srv = Server::new(Default::default());
+
+        assert_eq!(Some(1), attach(&mut srv, 0, 1));
+        assert_eq!(Some(1), srv.games[&1].host);
+
+        srv.games.get_mut(&1).unwrap().closed = true;
+        assert_eq!(None, attach(&mut srv, 1, 2));
+
+        srv.games.get_mut(&1).unwrap().closed = false;
+        assert_eq!(Some(2), attach(&mut srv, 1, 2));
+    }
+
+    #[tokio::test]
+    async fn refuse_attach_of_banned_addr() {
+        let listener = ::tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let _stream = ::tokio::net::TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, addr) = listener.accept().await.unwrap();
+        let (tx, _rx) = ::tokio::sync::mpsc::channel(1);
+        let client = crate::client::Client::new(stream, tx);
+        let client_id = client.id;
+
+        let mut srv = Server::new(Default::default());
+        srv.clients.push(client);
+        assert_eq!(Some(1), attach(&mut srv, 0, 1));
+
+        let until = std::time::Instant::now() + std::time::Duration::from_secs(60);
+        srv.games
+            .get_mut(&1)
+            .unwrap()
+            .ban("griefer", Some(addr.ip()), until);
+        assert_eq!(None, attach(&mut srv, 1, client_id));
+        assert_eq!(
+            Some(2),
+            attach(&mut srv, 1, 2),
+            "other addresses are welcome"
+        );
+    }
+}
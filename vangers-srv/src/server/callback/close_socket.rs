@@ -1,3 +1,5 @@
+use ::tracing::info;
+
 use crate::player::Status as PlayerStatus;
 use crate::protocol::{Action, Packet};
 // use crate::vanject::{VanjectError};
@@ -59,16 +61,26 @@ impl OnUpdate_CloseSocket for Server {
             );
         }
 
+        // the bind id is given to the next player, who must not get these objects
+        self.delete_player_objects(client_id);
+
         let game = self.get_mut_game_by_clientid(client_id).unwrap();
 
-        game.players.retain(|p| p.client_id != client_id);
+        let new_host = game.detach_player(client_id);
 
         // TODO: recalc game ratings
         // self.process_ratings(game.gmtype);
 
         if game.players.is_empty() {
             let game_id = game.id;
-            self.games.remove(&game_id);
+            if let Some(game) = self.games.remove(&game_id) {
+                self.record_game(&game);
+            }
+        }
+
+        if let Some(host) = new_host {
+            info!("client_id=`{}` is the new host", host);
+            self.notify_system(host, "You are the host of the game now, see /help");
         }
 
         Ok(OnUpdateOk::Complete)
@@ -3,6 +3,7 @@ use ::tracing::{debug, warn};
 use crate::Server;
 use crate::client::ClientID;
 use crate::protocol::{Action, NetTransportSend, Packet};
+use crate::server::anticheat::Violation;
 use crate::vanject::*;
 
 use super::{OnUpdateError, OnUpdateOk};
@@ -46,15 +47,30 @@ impl OnUpdate_CreateObject for Server {
         if game.vanjects.contains_key(&vanject.id) {
             debug!("VANJECT with id=`{}` already exists", vanject.id);
         } else {
+            let gmtype = game.get_gmtype();
             let player = game.get_mut_player(client_id).unwrap();
             if vanject.bind_to_player(player).is_err() {
                 return Err(CreateObjectError::PlayerNotBind(client_id).into());
             }
 
+            // objects of players are created in their own stations only
+            let station = vanject.get_station() as u8;
+            if is_owned_vanject(vanject.id) && station != vanject.player_bind_id {
+                let violation = Violation::WrongStation {
+                    object_id: vanject.id,
+                    station,
+                };
+                self.report_violation(client_id, violation);
+                return Ok(OnUpdateOk::Complete);
+            }
+            let player = game.get_mut_player(client_id).unwrap();
+
             if vanject.get_type() == NID::VANGER {
                 player.pos = vanject.pos;
+                // a new vanger (a respawn or another world) starts a new track
+                player.track = None;
 
-                if player.set_body(&vanject.body).is_err() {
+                if player.set_body(gmtype, &vanject.body).is_err() {
                     warn!("NID::VANGER: set body failed");
                 } else {
                     let data = vanject.to_vangers_byte();
@@ -67,7 +83,7 @@ impl OnUpdate_CreateObject for Server {
                         .collect::<Vec<_>>();
                     let player_position = Packet::new(Action::PLAYERS_POSITION, &data);
 
-                    self.notify_game(client_id, &answer);
+                    self.notify_world(client_id, vanject.id, &answer);
                     self.notify_game(client_id, &player_position);
                 }
             } else {
@@ -77,7 +93,7 @@ impl OnUpdate_CreateObject for Server {
 
                 if !vanject.is_players() {
                     // world->process_create;
-                    self.notify_game(client_id, &answer);
+                    self.notify_world(client_id, vanject.id, &answer);
                 } else {
                     if vanject.is_non_global() {
                         // world->process_create_inventory()
@@ -86,10 +102,10 @@ impl OnUpdate_CreateObject for Server {
                             &vanject.id.to_le_bytes(),
                             vanject.player_bind_id
                         );
-                        self.notify_game(client_id, &answer);
+                        self.notify_world(client_id, vanject.id, &answer);
                     } else {
                         // game->process_create_globals()
-                        self.notify_game(client_id, &answer);
+                        self.notify_world(client_id, vanject.id, &answer);
                     }
                 }
             }
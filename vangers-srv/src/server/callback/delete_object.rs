@@ -3,6 +3,7 @@ use ::tracing::debug;
 use crate::Server;
 use crate::client::ClientID;
 use crate::protocol::{Action, Packet};
+use crate::server::anticheat::Violation;
 use crate::utils::slice_le_to_i32;
 
 use super::{OnUpdateError, OnUpdateOk};
@@ -47,6 +48,17 @@ impl OnUpdate_DeleteObject for Server {
             }
         };
 
+        if let Some(owner) = game.object_owner(vanject_id)
+            && owner != player_auth_id
+        {
+            let violation = Violation::ForeignDelete {
+                object_id: vanject_id,
+                owner,
+            };
+            self.report_violation(client_id, violation);
+            return Ok(OnUpdateOk::Complete);
+        }
+
         let data = std::iter::empty()
             .chain(&vanject_id.to_le_bytes())
             .chain(&[player_auth_id])
@@ -76,7 +88,7 @@ impl OnUpdate_DeleteObject for Server {
             debug!("VANJECT with id=`{}` not found", vanject_id);
         }
 
-        self.notify_game(client_id, &answer);
+        self.notify_world(client_id, vanject_id, &answer);
         Ok(OnUpdateOk::Complete)
     }
 }
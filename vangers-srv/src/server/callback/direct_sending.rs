@@ -1,15 +1,18 @@
 use std::borrow::Cow;
-use std::io::Write;
 
 use tracing::warn;
 
 use crate::Server;
 use crate::client::ClientID;
-use crate::protocol::Packet;
+use crate::protocol::{LIMIT_MSG_LEN, Packet};
 use crate::utils;
 
 use super::{OnUpdateError, OnUpdateOk};
 
+/// Messages starting with it are sent to the teammates only in team games,
+/// see `Game::teammates`.
+const TEAM_CHAT_PREFIX: u8 = b'!';
+
 #[derive(Debug, ::thiserror::Error)]
 pub enum DirectSendingError {
     #[error("given data is too small")]
@@ -29,6 +32,54 @@ pub(super) trait OnUpdate_DirectSending {
     ) -> Result<OnUpdateOk, OnUpdateError>;
 }
 
+impl Server {
+    /// Returns receivers of the chat message `msg` (null-terminated CP866) sent by
+    /// `client_id` to `receivers` and the message to relay, `None` if it is not relayed:
+    /// chat commands, moderated messages.
+    fn route_chat<'a>(
+        &mut self,
+        client_id: ClientID,
+        mut receivers: Vec<ClientID>,
+        msg: Cow<'a, [u8]>,
+    ) -> Option<(Vec<ClientID>, Cow<'a, [u8]>)> {
+        // chat commands are run by the server and never relayed
+        if msg.first() == Some(&b'/') {
+            self.log_chat(client_id, &[], &msg[..msg.len() - 1]);
+            self.run_command(client_id, &msg[..msg.len() - 1]);
+            return None;
+        }
+
+        // team messages go to the teammates only whatever the mask is,
+        // outside of team games the prefix is a part of an ordinary message
+        let teammates = (msg.first() == Some(&TEAM_CHAT_PREFIX))
+            .then(|| self.get_game_by_clientid(client_id))
+            .flatten()
+            .and_then(|game| game.teammates(client_id));
+        let team_chat = teammates.is_some();
+        if let Some(teammates) = teammates {
+            receivers = teammates;
+        }
+
+        self.log_chat(client_id, &receivers, &msg[..msg.len() - 1]);
+
+        let text = utils::convert_cp866_to_utf8(&msg[usize::from(team_chat)..msg.len() - 1])
+            .unwrap_or_default();
+        let msg = match self.moderate(client_id, &text)? {
+            moderated if team_chat => {
+                let moderated = utils::convert_utf8_to_cp866(&format!("(team) {}", moderated));
+                Cow::Owned(utils::shrink_cstr(&moderated, LIMIT_MSG_LEN).into_owned())
+            }
+            moderated if moderated == text => msg,
+            moderated => {
+                let moderated = utils::convert_utf8_to_cp866(&moderated);
+                Cow::Owned(utils::shrink_cstr(&moderated, LIMIT_MSG_LEN).into_owned())
+            }
+        };
+
+        Some((receivers, msg))
+    }
+}
+
 impl OnUpdate_DirectSending for Server {
     #[tracing::instrument(skip_all)]
     fn direct_sending(
@@ -64,29 +115,23 @@ impl OnUpdate_DirectSending for Server {
             Err(DirectSendingError::String)?
         };
 
-        let mut msg = Cow::Borrowed(msg);
-
-        // TODO: take out to config this constant
-        const LIMIT_MSG_LEN: usize = 140;
         if msg.len() > LIMIT_MSG_LEN {
             warn!(
                 "direct message length is too big, max length: `{}`, given length: `{}`, the message will be cut",
                 LIMIT_MSG_LEN,
                 msg.len()
             );
-            msg = Cow::Owned({
-                let mut buffer = Vec::with_capacity(LIMIT_MSG_LEN);
-                buffer.write(&msg[0..LIMIT_MSG_LEN - 3 - 1]).ok();
-                buffer.write(b"...").ok();
-                buffer.push(0);
-                buffer
-            })
         }
+        let msg = utils::shrink_cstr(msg, LIMIT_MSG_LEN);
 
         let player_id = match player_id {
             Some(p_id) => p_id,
             None => return Err(DirectSendingError::TxPlayerNotFound(client_id).into()),
         };
+
+        let Some((client_ids, msg)) = self.route_chat(client_id, client_ids, msg) else {
+            return Ok(OnUpdateOk::Complete);
+        };
         let data = std::iter::empty()
             .chain(&[player_id])
             .chain(&msg[..])
@@ -106,3 +151,74 @@ impl OnUpdate_DirectSending for Server {
         Ok(OnUpdateOk::Complete)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chat_log::ChatLog;
+    use crate::game::{GameMode, Type as GameType};
+    use crate::player::Body as PlayerBody;
+    use crate::server::fixture::create_server;
+
+    fn create_chat_server(team_mode: i32) -> Server {
+        let mut srv = create_server(&["a", "b", "c"], Some(GameType::VAN_WAR));
+        let game = srv.games.get_mut(&1).unwrap();
+        if let Some(GameMode::VanWar(prm)) =
+            game.config.as_mut().map(|config| &mut config.game_type)
+        {
+            prm.team_mode = team_mode;
+        }
+
+        for (player, color) in game.players.iter_mut().zip([1, 1, 2]) {
+            let mut body = PlayerBody::default();
+            body.color = color;
+            player.body = Some(body);
+        }
+        srv
+    }
+
+    fn route(srv: &mut Server, msg: &[u8]) -> Option<(Vec<ClientID>, Vec<u8>)> {
+        srv.route_chat(11, vec![12, 13], Cow::Borrowed(msg))
+            .map(|(receivers, msg)| (receivers, msg.into_owned()))
+    }
+
+    #[test]
+    fn team_chat() {
+        let mut srv = create_chat_server(1);
+        let dir = std::env::temp_dir().join(format!("vangers-srv-team-{}", std::process::id()));
+        std::fs::remove_dir_all(&dir).ok();
+        srv.set_chat_log(ChatLog::open(&dir).unwrap());
+
+        assert_eq!(
+            Some((vec![12, 13], b"hi\0".to_vec())),
+            route(&mut srv, b"hi\0")
+        );
+        assert_eq!(
+            Some((vec![12], b"(team) hi\0".to_vec())),
+            route(&mut srv, b"!hi\0"),
+            "the teammates only"
+        );
+
+        let log = std::fs::read_dir(&dir).unwrap().next().unwrap().unwrap();
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert!(
+            lines[0].ends_with(r#"to=["b", "c"] text="hi""#),
+            "{}",
+            lines[0]
+        );
+        assert!(lines[1].ends_with(r#"to=["b"] text="!hi""#), "{}", lines[1]);
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn team_chat_in_non_team_game() {
+        let mut srv = create_chat_server(0);
+        assert_eq!(
+            Some((vec![12, 13], b"!hi\0".to_vec())),
+            route(&mut srv, b"!hi\0"),
+            "relayed as an ordinary message"
+        );
+    }
+}
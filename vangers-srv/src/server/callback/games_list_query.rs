@@ -1,10 +1,14 @@
-use std::ffi::CString;
+use std::fmt::Display;
+
+use ::tracing::warn;
 
 use super::{OnUpdateError, OnUpdateOk};
 use crate::Server;
 use crate::client::ClientID;
-use crate::game::Type as GameType;
+use crate::config::{GamesListConfig, GamesListSort};
+use crate::game::Game;
 use crate::protocol::Packet;
+use crate::utils::{convert_cp866_to_utf8, convert_utf8_to_cp866, render, shrink_cstr};
 
 #[allow(non_camel_case_types)]
 pub(super) trait OnUpdate_GamesListQuery {
@@ -22,50 +26,43 @@ impl OnUpdate_GamesListQuery for Server {
         packet: &Packet,
         _client_id: ClientID,
     ) -> Result<OnUpdateOk, OnUpdateError> {
-        let mut games_count: u8 = 0;
-        let mut data = vec![games_count];
-        for game in self.games.values() {
-            if !game.is_configured() {
-                continue;
+        let conf = &self.conf.games_list;
+
+        let mut games = self
+            .games
+            .values()
+            .filter(|game| game.is_configured())
+            .filter(|game| !(conf.hide_full && game.is_full()))
+            .filter(|game| match conf.max_age_hours {
+                Some(hours) => game.birth_time.duration().as_secs() < u64::from(hours) * 3600,
+                None => true,
+            })
+            .collect::<Vec<_>>();
+
+        match conf.sort {
+            GamesListSort::Id => games.sort_by_key(|game| game.id),
+            GamesListSort::Newest => games.sort_by_key(|game| std::cmp::Reverse(game.id)),
+            GamesListSort::Players => {
+                games.sort_by_key(|game| (std::cmp::Reverse(game.players.len()), game.id))
             }
+            GamesListSort::Name => games.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id))),
+        }
 
-            let str_gmtype = match game.get_gmtype() {
-                GameType::MECHOSOMA => 'M',
-                GameType::VAN_WAR => 'V',
-                GameType::PASSEMBLOSS => 'P',
-                GameType::MIR_RAGE => 'R',
-                _ => '?',
-            };
-
-            let title_head = String::from("[Rust-SRV] ");
-            let title_tail = format!(
-                ": {} {} {}",
-                game.players.len(),
-                str_gmtype,
-                game.birth_time
+        // the count of games is sent as `u8`
+        if games.len() > u8::MAX as usize {
+            warn!(
+                "too many games to list: `{}`, only first `{}` will be sent",
+                games.len(),
+                u8::MAX
             );
-
-            let title = match game.name.last() {
-                Some(0) => &game.name[..game.name.len() - 1],
-                Some(_) => &game.name[..],
-                None => b"[UNDEFINED TITLE]",
-            };
-
-            let mut name = std::iter::empty()
-                .chain(CString::new(title_head).unwrap().as_bytes())
-                .chain(title)
-                .chain(CString::new(title_tail).unwrap().as_bytes())
-                .chain(&[0]) // null terminator of the c-style string
-                .copied()
-                .collect::<Vec<_>>();
-
-            data.append(&mut game.id.to_le_bytes().to_vec());
-            data.append(&mut name);
-
-            games_count += 1;
+            games.truncate(u8::MAX as usize);
         }
 
-        data[0] = games_count;
+        let mut data = vec![games.len() as u8];
+        for game in games {
+            data.extend_from_slice(&game.id.to_le_bytes());
+            data.extend_from_slice(&get_title(conf, game));
+        }
 
         packet
             .create_answer(data)
@@ -74,11 +71,46 @@ impl OnUpdate_GamesListQuery for Server {
     }
 }
 
+/// Returns null-terminated CP866 title of the `game` based on `conf.title` template.
+fn get_title(conf: &GamesListConfig, game: &Game) -> Vec<u8> {
+    let name = match game.name.last() {
+        Some(0) => &game.name[..game.name.len() - 1],
+        Some(_) => &game.name[..],
+        None => b"[UNDEFINED TITLE]",
+    };
+    let name = convert_cp866_to_utf8(name).unwrap_or_default();
+    let lock = if game.is_locked() {
+        conf.lock.as_str()
+    } else {
+        ""
+    };
+    let closed = if game.closed {
+        conf.closed.as_str()
+    } else {
+        ""
+    };
+
+    let vars: &[(&str, &dyn Display)] = &[
+        ("name", &name),
+        ("lock", &lock),
+        ("closed", &closed),
+        ("players", &game.players.len()),
+        ("max", &game.max_players),
+        ("type", &conf.types.get(game.get_gmtype())),
+        ("time", &game.birth_time),
+        ("id", &game.id),
+    ];
+
+    let title = convert_utf8_to_cp866(&render(&conf.title, vars));
+    shrink_cstr(&title, conf.title_max_len).into_owned()
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::game::Config;
-    use crate::game::Game;
+    use crate::game::Lock;
+    use crate::game::Type as GameType;
     use crate::player::{Body as PlayerBody, Player};
     use crate::protocol::Action;
 
@@ -106,7 +138,7 @@ mod test {
             game.config = Some(Config::new(GameType::PASSEMBLOSS));
 
             let mut player = Player::new(11);
-            player.set_auth(b"player\0", b"\0");
+            player.set_auth(b"player\0", None);
             player.body = Some(PlayerBody::default());
 
             game.attach_player(player);
@@ -132,12 +164,12 @@ mod test {
                 game.config = Some(Config::new(GameType::PASSEMBLOSS));
 
                 let mut player = Player::new(21);
-                player.set_auth(b"player1\0", b"\0");
+                player.set_auth(b"player1\0", None);
                 player.body = Some(PlayerBody::default());
                 game.attach_player(player);
 
                 let mut player = Player::new(22);
-                player.set_auth(b"player2\0", b"\0");
+                player.set_auth(b"player2\0", None);
                 player.body = Some(PlayerBody::default());
                 game.attach_player(player);
 
@@ -201,7 +233,7 @@ mod test {
                 assert_eq!(p.data[1..5], 1u32.to_le_bytes()); // id of the single game
                 assert_eq!(
                     &p.data[5..],
-                    b"[Rust-SRV] [UNDEFINED TITLE]: 1 P 0:00:00\0".to_vec()
+                    b"[Rust-SRV] [UNDEFINED TITLE]: 1/30 P 0:00:00\0".to_vec()
                 );
             }
             t => panic!("unexpected responsed type: {:?}", t),
@@ -222,10 +254,130 @@ mod test {
                 assert_eq!(p.data[1..5], 2u32.to_le_bytes()); // id of the single game
                 assert_eq!(
                     &p.data[5..],
-                    b"[Rust-SRV] [UNDEFINED TITLE]: 2 P 0:00:00\0".to_vec()
+                    b"[Rust-SRV] [UNDEFINED TITLE]: 2/30 P 0:00:00\0".to_vec()
                 );
             }
             t => panic!("unexpected responsed type: {:?}", t),
         }
     }
+
+    fn create_game(id: u32, name: &[u8], gmtype: GameType, players: usize) -> Game {
+        let mut game = Game::new(id);
+        game.name = name.to_vec();
+        game.config = Some(Config::new(gmtype));
+        for i in 0..players {
+            game.attach_player(Player::new(id as usize * 100 + i));
+        }
+        game
+    }
+
+    /// Returns `(game_id, title)` pairs of the response.
+    fn query(srv: &mut Server) -> Vec<(u32, Vec<u8>)> {
+        let request = Packet::new(Action::GAMES_LIST_QUERY, &[]);
+        let data = match srv.games_list_query(&request, 1).unwrap() {
+            OnUpdateOk::Response(p) => p.data,
+            t => panic!("unexpected responsed type: {:?}", t),
+        };
+
+        let mut games = vec![];
+        let mut rest = &data[1..];
+        while !rest.is_empty() {
+            let id = u32::from_le_bytes(rest[0..4].try_into().unwrap());
+            let len = rest[4..].iter().position(|&b| b == 0).unwrap() + 1;
+            games.push((id, rest[4..4 + len].to_vec()));
+            rest = &rest[4 + len..];
+        }
+
+        assert_eq!(data[0] as usize, games.len());
+        games
+    }
+
+    #[test]
+    fn title_template_and_all_types() {
+        let mut srv = Server::new(Default::default());
+        srv.conf.games_list.title =
+            "#{id} {lock}{closed}{name} [{type}] {players}/{max}".to_string();
+
+        let mut game = create_game(1, b"hunt\0", GameType::HUNTAGE, 1);
+        game.closed = true;
+        srv.games.insert(1, game);
+        let mut game = create_game(2, b"must\0", GameType::MUSTODONT, 2);
+        game.lock = Some(Lock::Creator(200));
+        srv.games.insert(2, game);
+        srv.games
+            .insert(3, create_game(3, b"\x8f\xe0\xa8\0", GameType::VAN_WAR, 1));
+
+        assert_eq!(
+            vec![
+                (1, b"#1 [closed] hunt [H] 1/30\0".to_vec()),
+                (2, b"#2 [locked] must [U] 2/30\0".to_vec()),
+                (3, b"#3 \x8f\xe0\xa8 [V] 1/30\0".to_vec()),
+            ],
+            query(&mut srv)
+        );
+    }
+
+    #[test]
+    fn title_is_truncated() {
+        let mut srv = Server::new(Default::default());
+        srv.conf.games_list.title = "{name}".to_string();
+        srv.conf.games_list.title_max_len = 16;
+
+        let name = [b'a'; 100].iter().chain(&[0]).copied().collect::<Vec<_>>();
+        srv.games
+            .insert(1, create_game(1, &name, GameType::VAN_WAR, 1));
+
+        let games = query(&mut srv);
+        assert_eq!(b"aaaaaaaaaaaa...\0".to_vec(), games[0].1);
+    }
+
+    #[test]
+    fn hide_full_games() {
+        let mut srv = Server::new(Default::default());
+        srv.games
+            .insert(1, create_game(1, b"full\0", GameType::VAN_WAR, 30));
+        srv.games
+            .insert(2, create_game(2, b"free\0", GameType::VAN_WAR, 29));
+
+        assert_eq!(2, query(&mut srv).len());
+
+        srv.conf.games_list.hide_full = true;
+        let games = query(&mut srv);
+        assert_eq!(1, games.len());
+        assert_eq!(2, games[0].0);
+    }
+
+    #[test]
+    fn sort() {
+        let mut srv = Server::new(Default::default());
+        srv.games
+            .insert(1, create_game(1, b"b\0", GameType::VAN_WAR, 1));
+        srv.games
+            .insert(2, create_game(2, b"c\0", GameType::VAN_WAR, 3));
+        srv.games
+            .insert(3, create_game(3, b"a\0", GameType::VAN_WAR, 2));
+
+        let ids = |srv: &mut Server| query(srv).iter().map(|g| g.0).collect::<Vec<_>>();
+
+        assert_eq!(vec![1, 2, 3], ids(&mut srv));
+        srv.conf.games_list.sort = GamesListSort::Newest;
+        assert_eq!(vec![3, 2, 1], ids(&mut srv));
+        srv.conf.games_list.sort = GamesListSort::Players;
+        assert_eq!(vec![2, 3, 1], ids(&mut srv));
+        srv.conf.games_list.sort = GamesListSort::Name;
+        assert_eq!(vec![3, 1, 2], ids(&mut srv));
+    }
+
+    #[test]
+    fn games_count_overflow() {
+        let mut srv = Server::new(Default::default());
+        for id in 1..=300 {
+            srv.games
+                .insert(id, create_game(id, b"g\0", GameType::VAN_WAR, 1));
+        }
+
+        let games = query(&mut srv);
+        assert_eq!(255, games.len());
+        assert_eq!(255, games.last().unwrap().0);
+    }
 }
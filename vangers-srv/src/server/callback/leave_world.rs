@@ -76,8 +76,8 @@ impl OnUpdate_LeaveWorld for Server {
 
         game.vanjects.retain(|id, _| !delete.contains_key(id));
 
-        for (_, packet) in delete {
-            self.notify_game(client_id, &packet);
+        for (id, packet) in delete {
+            self.notify_world(client_id, id, &packet);
         }
 
         // That sends by github server, but it seems to may be safety removed at all
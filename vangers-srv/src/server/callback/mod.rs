@@ -22,6 +22,7 @@ use direct_sending::*;
 use games_list_query::*;
 use get_game_data::*;
 use leave_world::*;
+pub(in crate::server) use register_name::CheckedPasswords;
 use register_name::*;
 use server_time_query::*;
 use set_game_data::*;
@@ -32,9 +33,10 @@ use update_object::*;
 
 use ::tracing::{debug, error, info, trace, warn};
 
+use crate::Server;
 use crate::client::ClientID;
+use crate::config::ServerConfig;
 use crate::protocol::{Action, Packet};
-use crate::{Server, ServerConfig};
 
 #[derive(Debug, ::thiserror::Error)]
 pub enum OnUpdateError {
@@ -108,8 +110,38 @@ impl OnUpdate for Server {
         )
     )]
     fn on_update(&mut self, client_id: ClientID, packet: Packet) {
+        // packets read before the client was kicked
+        if !self.clients.iter().any(|c| c.id == client_id) {
+            warn!("drop packet of disconnected client_id=`{}`", client_id);
+            return;
+        }
+
+        // `REGISTER_NAME` completes on the event loop later,
+        // the packets after it must not overtake it
+        if let Some(pending) = self.pending_registers.get_mut(&client_id) {
+            debug!(
+                "hold {:?} of client_id=`{}` until its passwords are checked",
+                packet.action, client_id
+            );
+            pending.push(packet);
+            return;
+        }
+
         view("[<-]", &packet, &self.conf);
 
+        if needs_admission(packet.action)
+            && self
+                .games
+                .get_game_by_client_id(client_id)
+                .is_some_and(|game| !game.is_admitted(client_id))
+        {
+            warn!(
+                "drop {:?} of client_id=`{}`: the password of the private game is not checked yet",
+                packet.action, client_id
+            );
+            return;
+        }
+
         let result = match packet.action {
             Action::ATTACH_TO_GAME => self.attach_to_game(&packet, client_id),
             Action::SERVER_TIME_QUERY => self.server_time_query(&packet, client_id),
@@ -153,6 +185,17 @@ impl OnUpdate for Server {
     }
 }
 
+/// Returns `true` for actions of players of a game, which are refused in a private
+/// game until the password is checked by `REGISTER_NAME`, see `Game::is_admitted`.
+fn needs_admission(action: Action) -> bool {
+    use Action::*;
+
+    !matches!(
+        action,
+        ATTACH_TO_GAME | SERVER_TIME_QUERY | GAMES_LIST_QUERY | REGISTER_NAME | CLOSE_SOCKET
+    )
+}
+
 fn view(prefix: &str, p: &Packet, conf: &ServerConfig) {
     use Action::*;
 
@@ -161,12 +204,12 @@ fn view(prefix: &str, p: &Packet, conf: &ServerConfig) {
             trace!("{} {:?}: {:X?}", prefix, a, &p.data);
         }
         a @ (SERVER_TIME | SERVER_TIME_QUERY | SERVER_TIME_RESPONSE) => {
-            if !conf.supress_log_server_time {
+            if !conf.log.supress_server_time {
                 trace!("{} {:?}: {:X?}", prefix, a, &p.data);
             }
         }
         a @ GAMES_LIST_QUERY => {
-            if !conf.supress_log_games_list_query {
+            if !conf.log.supress_games_list_query {
                 debug!("{} {:?}", prefix, a)
             }
         }
@@ -176,3 +219,60 @@ fn view(prefix: &str, p: &Packet, conf: &ServerConfig) {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::{Game, Lock};
+    use crate::player::Player;
+
+    #[test]
+    fn drop_packets_of_disconnected_clients() {
+        let mut srv = Server::new(Default::default());
+        let mut game = Game::new(1);
+        game.attach_player(Player::new(11));
+        srv.games.insert(1, game);
+
+        // the client was kicked, but its packets are still in the channel
+        srv.on_update(11, Packet::new(Action::CLOSE_SOCKET, &[]));
+        assert_eq!(1, srv.games[&1].players.len());
+    }
+
+    #[tokio::test]
+    async fn hold_packets_until_passwords_are_checked() {
+        let listener = ::tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .unwrap();
+        let _stream = ::tokio::net::TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, _) = listener.accept().await.unwrap();
+        let (tx, _rx) = ::tokio::sync::mpsc::channel(1);
+        let client = crate::client::Client::new(stream, tx);
+        let client_id = client.id;
+
+        let mut srv = Server::new(Default::default());
+        srv.clients.push(client);
+        let mut game = Game::new(1);
+        game.lock = Some(Lock::Creator(client_id));
+        game.attach_player(Player::new(client_id));
+        srv.games.insert(1, game);
+        let (checks_tx, mut checks_rx) = ::tokio::sync::mpsc::unbounded_channel();
+        srv.password_checks = Some(checks_tx);
+
+        srv.on_update(
+            client_id,
+            Packet::new(Action::REGISTER_NAME, b"creator\0secret\0"),
+        );
+        let set_world = [&[0][..], &16384i16.to_le_bytes()].concat();
+        srv.on_update(client_id, Packet::new(Action::SET_WORLD, &set_world));
+        let in_world = |srv: &Server| srv.games[&1].get_player(client_id).unwrap().world.is_some();
+        assert!(!in_world(&srv), "held while the password is checked");
+
+        let checked = checks_rx.recv().await.unwrap();
+        srv.finish_register(checked);
+        assert!(matches!(srv.games[&1].lock, Some(Lock::Password(_))));
+        assert!(in_world(&srv), "handled after the check");
+        assert!(srv.pending_registers.is_empty());
+    }
+}
@@ -3,13 +3,21 @@ use std::{
     ffi::{CStr, CString},
 };
 
-use ::tracing::info;
+use ::tracing::{error, info, warn};
 
 use crate::Server;
+use crate::accounts::{Checked, Login};
 use crate::client::ClientID;
+use crate::config::OnNameMismatch;
+use crate::game::{GameID, Lock};
+use crate::player::{PasswordHash, guest_name};
 use crate::protocol::Packet;
+use crate::utils::convert_cp866_to_utf8;
 
-use super::{OnUpdateError, OnUpdateOk};
+use super::{OnUpdate, OnUpdateError, OnUpdateOk};
+
+/// Max length of a name (without null-terminator) given by the server.
+const MAX_NAME_LEN: usize = 15;
 
 #[derive(Debug, ::thiserror::Error)]
 pub enum RegisterNameError {
@@ -21,6 +29,73 @@ pub enum RegisterNameError {
     PlayerNotBind(ClientID),
     #[error("request to set empty name")]
     NameIsNull,
+    #[error("wrong password for private game_id=`{0}`")]
+    WrongPassword(GameID),
+    #[error("private game_id=`{0}` has no password yet")]
+    NoPasswordYet(GameID),
+    #[error("name {0:?} is banned in game_id=`{1}`")]
+    Banned(String, GameID),
+    #[error("name {0:?} is reserved by another password")]
+    NameReserved(String),
+}
+
+/// Password checks of a `REGISTER_NAME`. Hashing is slow, so they are run
+/// on the blocking thread pool, then `Server::complete_register` is called
+/// with the result on the event loop.
+#[derive(Debug)]
+pub(in crate::server) struct PasswordCheck {
+    client_id: ClientID,
+    packet: Packet,
+    login: CString,
+    pwd: CString,
+    /// The lock of the game at the moment of the request.
+    lock: Option<Lock>,
+    /// The password is hashed for a private game of the player.
+    hash: bool,
+    /// The hash of the account of the name, `Some(None)` if the name is free,
+    /// `None` if accounts are disabled.
+    account: Option<Option<PasswordHash>>,
+}
+
+/// Result of `PasswordCheck::run`.
+#[derive(Debug)]
+pub(in crate::server) struct CheckedPasswords {
+    client_id: ClientID,
+    packet: Packet,
+    login: CString,
+    /// The lock the password was checked against.
+    lock: Option<Lock>,
+    /// `true` if the password matches `lock`.
+    unlocked: bool,
+    /// The hash of a non-empty password if it was requested.
+    hash: Option<PasswordHash>,
+    account: Option<Checked>,
+}
+
+impl PasswordCheck {
+    pub(in crate::server) fn run(self) -> CheckedPasswords {
+        let pwd = self.pwd.to_bytes();
+
+        let unlocked = match self.lock {
+            Some(Lock::Password(ref hash)) => hash.verify(pwd),
+            Some(Lock::Preset(ref preset)) => preset.as_bytes() == pwd,
+            Some(Lock::Creator(_)) | None => true,
+        };
+        let hash = (self.hash && !pwd.is_empty()).then(|| PasswordHash::new(pwd));
+        let account = self
+            .account
+            .map(|account| Checked::new(account.as_ref(), pwd));
+
+        CheckedPasswords {
+            client_id: self.client_id,
+            packet: self.packet,
+            login: self.login,
+            lock: self.lock,
+            unlocked,
+            hash,
+            account,
+        }
+    }
 }
 
 #[allow(non_camel_case_types)]
@@ -39,6 +114,159 @@ impl OnUpdate_RegisterName for Server {
         packet: &Packet,
         client_id: ClientID,
     ) -> Result<OnUpdateOk, OnUpdateError> {
+        let addr = self.addr_of(client_id);
+        let game = self
+            .games
+            .get_game_by_client_id(client_id)
+            .ok_or(RegisterNameError::PlayerNotFound(client_id))?;
+        let (login, pwd) = extract_auth_data(&packet.data)?;
+
+        let name = convert_cp866_to_utf8(login.to_bytes()).unwrap_or_default();
+        if game.is_banned(Some(&name), addr) {
+            let game_id = game.id;
+            self.kick(client_id, "You are banned in this game for a while");
+            return Err(RegisterNameError::Banned(name, game_id).into());
+        }
+
+        // the creator of a private game may have not configured it yet
+        let creator = match game.lock {
+            Some(Lock::Creator(creator_id)) => creator_id == client_id,
+            _ => !game.is_configured(),
+        };
+        let check = PasswordCheck {
+            client_id,
+            packet: packet.clone(),
+            login: login.into_owned(),
+            pwd: pwd.to_owned(),
+            lock: game.lock.clone(),
+            hash: creator,
+            account: self
+                .accounts
+                .as_ref()
+                .map(|accounts| accounts.pwd_of(&name)),
+        };
+
+        match self.password_checks {
+            Some(ref tx) => {
+                let tx = tx.clone();
+                ::tokio::task::spawn_blocking(move || tx.send(check.run()).ok());
+                self.pending_registers.insert(client_id, vec![]);
+                Ok(OnUpdateOk::Complete)
+            }
+            // no event loop to come back to
+            None => self.complete_register(check.run()),
+        }
+    }
+}
+
+impl Server {
+    /// Completes `REGISTER_NAME` checked on the blocking thread pool, then handles
+    /// packets the client has sent meanwhile in their order.
+    pub(in crate::server) fn finish_register(&mut self, checked: CheckedPasswords) {
+        let client_id = checked.client_id;
+        if let Err(err) = self.complete_register(checked) {
+            error!("{}", err);
+        }
+
+        // packets after another `REGISTER_NAME` are held again
+        let pending = self.pending_registers.remove(&client_id);
+        for packet in pending.into_iter().flatten() {
+            self.on_update(client_id, packet);
+        }
+    }
+
+    /// Registers the name of `checked.client_id` when its passwords
+    /// are checked, see `PasswordCheck`.
+    pub(in crate::server) fn complete_register(
+        &mut self,
+        checked: CheckedPasswords,
+    ) -> Result<OnUpdateOk, OnUpdateError> {
+        let CheckedPasswords {
+            client_id,
+            ref packet,
+            mut login,
+            ..
+        } = checked;
+
+        // the client may be gone while its passwords were checked
+        let game = self
+            .games
+            .get_mut_game_by_client_id(client_id)
+            .ok_or(RegisterNameError::PlayerNotFound(client_id))?;
+
+        match game.lock {
+            Some(Lock::Creator(creator_id)) if creator_id == client_id => {
+                game.lock = match checked.hash {
+                    Some(ref hash) => Some(Lock::Password(hash.clone())),
+                    None => {
+                        warn!("creator of private game_id=`{}` has no password", game.id);
+                        None
+                    }
+                };
+            }
+            Some(Lock::Creator(_)) => {
+                let game_id = game.id;
+                self.kick(client_id, "The private game has no password yet, try again");
+                return Err(RegisterNameError::NoPasswordYet(game_id).into());
+            }
+            // the lock may be changed while the password was checked
+            Some(ref lock) if !checked.unlocked || checked.lock.as_ref() != Some(lock) => {
+                let game_id = game.id;
+                self.kick(client_id, "Wrong password for the private game");
+                return Err(RegisterNameError::WrongPassword(game_id).into());
+            }
+            _ => {}
+        }
+
+        let name = convert_cp866_to_utf8(login.to_bytes()).unwrap_or_default();
+        if let Some(ref mut accounts) = self.accounts
+            && let Some(account) = checked.account
+        {
+            match accounts.login(&name, account) {
+                Ok(Login::WrongPassword) => match self.conf.accounts.on_mismatch {
+                    OnNameMismatch::Reject => {
+                        self.kick(client_id, &format!("The name `{}` is reserved", name));
+                        return Err(RegisterNameError::NameReserved(name).into());
+                    }
+                    OnNameMismatch::Rename => {
+                        login = guest_name();
+                        let renamed = login.to_string_lossy();
+                        info!("name {:?} is reserved, renamed to {:?}", name, renamed);
+                        self.notify_system(
+                            client_id,
+                            &format!("The name `{}` is reserved, you are `{}`", name, renamed),
+                        );
+                    }
+                },
+                Ok(Login::Claimed) => info!("name {:?} has been reserved", name),
+                Ok(_) => {}
+                Err(err) => error!("{}", err),
+            }
+        }
+
+        let game = self
+            .games
+            .get_mut_game_by_client_id(client_id)
+            .ok_or(RegisterNameError::PlayerNotFound(client_id))?;
+
+        let taken = game
+            .players
+            .iter()
+            .filter(|p| p.client_id != client_id)
+            .filter_map(|p| p.auth.as_ref())
+            .map(|auth| auth.name_utf8().to_lowercase())
+            .collect::<Vec<_>>();
+        if let Some(unique) = unique_name(&login, &taken) {
+            let msg = format!(
+                "The name `{}` is already taken in this game, you are `{}`",
+                convert_cp866_to_utf8(login.to_bytes()).unwrap_or_default(),
+                convert_cp866_to_utf8(unique.to_bytes()).unwrap_or_default()
+            );
+            info!("name {:?} is taken, renamed to {:?}", login, unique);
+            login = unique;
+            self.notify_system(client_id, &msg);
+        }
+
         let player = self
             .games
             .get_mut_player_by_client_id(client_id)
@@ -49,9 +277,7 @@ impl OnUpdate_RegisterName for Server {
             .map(|bind| bind.id())
             .ok_or(RegisterNameError::PlayerNotBind(client_id))?;
 
-        let (login, pwd) = extract_auth_data(&packet.data)?;
-
-        player.set_auth(login.to_bytes_with_nul(), pwd.to_bytes_with_nul());
+        player.set_auth(login.to_bytes_with_nul(), checked.hash);
         info!("set name {:?} for player_id=`{}`", login, player_bind_id);
 
         let data = std::iter::empty()
@@ -67,6 +293,31 @@ impl OnUpdate_RegisterName for Server {
     }
 }
 
+/// Returns `None` if `name` is not `taken` (lowercase UTF-8 names), otherwise
+/// the first free name with a suffix like `Fenex(2)`. The name is cut to fit
+/// the suffix into `MAX_NAME_LEN` bytes.
+fn unique_name(name: &CStr, taken: &[String]) -> Option<CString> {
+    let is_taken = |name: &[u8]| {
+        let name = convert_cp866_to_utf8(name)
+            .unwrap_or_default()
+            .to_lowercase();
+        taken.contains(&name)
+    };
+
+    if !is_taken(name.to_bytes()) {
+        return None;
+    }
+
+    (2..)
+        .map(|n| {
+            let suffix = format!("({})", n);
+            let len = name.count_bytes().min(MAX_NAME_LEN - suffix.len());
+            [&name.to_bytes()[..len], suffix.as_bytes()].concat()
+        })
+        .find(|name| !is_taken(name))
+        .map(|name| CString::new(name).expect("the `name` has no null-terminators"))
+}
+
 fn extract_auth_data<'a>(data: &'a [u8]) -> Result<(Cow<'a, CStr>, &'a CStr), RegisterNameError> {
     let mut name = Cow::Borrowed(
         CStr::from_bytes_until_nul(&data).map_err(|_| RegisterNameError::NameOrPasswordParse)?,
@@ -83,15 +334,11 @@ fn extract_auth_data<'a>(data: &'a [u8]) -> Result<(Cow<'a, CStr>, &'a CStr), Re
     let pwd = CStr::from_bytes_until_nul(&data[name.to_bytes_with_nul().len()..])
         .map_err(|_| RegisterNameError::NameOrPasswordParse)?;
 
-    {
-        // TODO: create auth service and checks for correct pwd here
-    }
-
-    if name.count_bytes() > 16 {
+    if name.count_bytes() > MAX_NAME_LEN + 1 {
         name = CString::from_vec_with_nul(
             name.to_bytes_with_nul()
                 .into_iter()
-                .take(15)
+                .take(MAX_NAME_LEN)
                 .chain(&[0])
                 .copied()
                 .collect::<Vec<_>>(),
@@ -122,8 +369,169 @@ fn extract_auth_data<'a>(data: &'a [u8]) -> Result<(Cow<'a, CStr>, &'a CStr), Re
 }
 
 #[cfg(test)]
-mod tests {
+mod test {
     use super::*;
+    use crate::game::Game;
+    use crate::player::Player;
+    use crate::protocol::Action;
+
+    fn register(srv: &mut Server, client_id: ClientID, data: &[u8]) -> bool {
+        let request = Packet::new(Action::REGISTER_NAME, data);
+        srv.register_name(&request, client_id).is_ok()
+    }
+
+    #[test]
+    fn private_game() {
+        let mut srv = Server::new(Default::default());
+        let mut game = Game::new(1);
+        game.lock = Some(Lock::Creator(11));
+        for client_id in [11, 12, 13, 14] {
+            game.attach_player(Player::new(client_id));
+        }
+        srv.games.insert(1, game);
+
+        assert!(register(&mut srv, 11, b"creator\0secret\0"));
+        assert!(matches!(srv.games[&1].lock, Some(Lock::Password(_))));
+
+        assert!(register(&mut srv, 12, b"friend\0secret\0"));
+        assert!(!register(&mut srv, 13, b"stranger\0guess\0"));
+        assert!(!register(&mut srv, 14, b"stranger\0\0"));
+
+        let client_ids = srv.games[&1]
+            .players
+            .iter()
+            .map(|p| p.client_id)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![11, 12], client_ids);
+    }
+
+    #[test]
+    fn private_game_creator_without_password() {
+        let mut srv = Server::new(Default::default());
+        let mut game = Game::new(1);
+        game.lock = Some(Lock::Creator(11));
+        game.attach_player(Player::new(11));
+        srv.games.insert(1, game);
+
+        assert!(register(&mut srv, 11, b"creator\0\0"));
+        assert!(!srv.games[&1].is_locked());
+    }
+
+    #[test]
+    fn private_game_preset() {
+        let mut srv = Server::new(Default::default());
+        let mut game = Game::new(1);
+        game.lock = Some(Lock::Preset("secret".to_string()));
+        for client_id in [11, 12] {
+            game.attach_player(Player::new(client_id));
+        }
+        srv.games.insert(1, game);
+
+        assert!(register(&mut srv, 11, b"member\0secret\0"));
+        assert!(!register(&mut srv, 12, b"stranger\0guess\0"));
+        assert!(srv.games.get_player_by_client_id(12).is_none());
+    }
+
+    #[test]
+    fn private_game_without_password_yet() {
+        let mut srv = Server::new(Default::default());
+        let mut game = Game::new(1);
+        game.lock = Some(Lock::Creator(11));
+        for client_id in [11, 12] {
+            game.attach_player(Player::new(client_id));
+        }
+        srv.games.insert(1, game);
+
+        assert!(!register(&mut srv, 12, b"early\0\0"));
+        assert!(srv.games.get_player_by_client_id(12).is_none());
+    }
+
+    #[test]
+    fn unique_names() {
+        let taken = ["fenex".to_string(), "fenex(2)".to_string()];
+        assert_eq!(None, unique_name(c"Other", &taken));
+        assert_eq!(Some(c"FENEX(3)".into()), unique_name(c"FENEX", &taken));
+
+        // cyrillic names are compared case-insensitively after CP866 decoding
+        let taken = ["фенекс".to_string()];
+        assert_eq!(
+            Some(c"\x94\x85\x8d\x85\x8a\x91(2)".into()),
+            unique_name(c"\x94\x85\x8d\x85\x8a\x91", &taken)
+        );
+
+        let taken = ["123456789_12345".to_string()];
+        assert_eq!(
+            Some(c"123456789_12(2)".into()),
+            unique_name(c"123456789_12345", &taken)
+        );
+    }
+
+    #[test]
+    fn duplicated_names_in_game() {
+        let mut srv = Server::new(Default::default());
+        let mut game = Game::new(1);
+        for client_id in [11, 12, 13] {
+            game.attach_player(Player::new(client_id));
+        }
+        srv.games.insert(1, game);
+
+        assert!(register(&mut srv, 11, b"Fenex\0\0"));
+        assert!(register(&mut srv, 12, b"fenex\0\0"));
+        assert!(register(&mut srv, 13, b"FENEX\0\0"));
+        // the same player registers again with its own name
+        assert!(register(&mut srv, 11, b"Fenex\0\0"));
+
+        let names = srv.games[&1]
+            .players
+            .iter()
+            .map(|p| p.auth.as_ref().unwrap().name_utf8())
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["Fenex", "fenex(2)", "FENEX(3)"], names);
+    }
+
+    #[test]
+    fn reserved_name() {
+        let mut srv = Server::new(Default::default());
+        srv.set_accounts(Default::default());
+        let mut game = Game::new(1);
+        for client_id in [11, 12, 13] {
+            game.attach_player(Player::new(client_id));
+        }
+        srv.games.insert(1, game);
+
+        assert!(register(&mut srv, 11, b"owner\0secret\0"));
+        assert!(register(&mut srv, 12, b"owner\0guess\0"));
+
+        let name = |srv: &Server, client_id| {
+            let (_, player) = srv.games.get_player_by_client_id(client_id).unwrap();
+            player.auth.as_ref().unwrap().name_utf8()
+        };
+        assert_eq!("owner", name(&srv, 11));
+        assert!(name(&srv, 12).starts_with("Player-"));
+
+        srv.conf.accounts.on_mismatch = OnNameMismatch::Reject;
+        assert!(!register(&mut srv, 13, b"OWNER\0\0"));
+        assert!(srv.games.get_player_by_client_id(13).is_none());
+    }
+
+    #[test]
+    fn banned_name() {
+        let mut srv = Server::new(Default::default());
+        let mut game = Game::new(1);
+        game.ban(
+            "Griefer",
+            None,
+            std::time::Instant::now() + std::time::Duration::from_secs(60),
+        );
+        for client_id in [11, 12] {
+            game.attach_player(Player::new(client_id));
+        }
+        srv.games.insert(1, game);
+
+        assert!(!register(&mut srv, 11, b"GRIEFER\0\0"));
+        assert!(srv.games.get_player_by_client_id(11).is_none());
+        assert!(register(&mut srv, 12, b"Fenex\0\0"));
+    }
 
     #[test]
     fn correct() {
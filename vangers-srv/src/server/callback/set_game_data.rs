@@ -1,10 +1,13 @@
 use std::ffi::CStr;
 
-use ::tracing::{error, info};
+use ::tracing::{error, info, warn};
 
 use crate::Server;
 use crate::client::ClientID;
+use crate::config::GameAccess;
+use crate::game::Lock;
 use crate::protocol::Packet;
+use crate::utils::convert_cp866_to_utf8;
 
 use super::{OnUpdateError, OnUpdateOk};
 
@@ -38,6 +41,7 @@ impl OnUpdate_SetGameData for Server {
         packet: &Packet,
         client_id: ClientID,
     ) -> Result<OnUpdateOk, OnUpdateError> {
+        let conf = &self.conf;
         let game = match self.games.get_mut_game_by_client_id(client_id) {
             Some(g) if g.is_configured() => Err(SetGameDataError::AlreadyConfigured(g.id))?,
             Some(g) => g,
@@ -63,6 +67,37 @@ impl OnUpdate_SetGameData for Server {
             })
             .map_err(SetGameDataError::SliceToGameConfigParse)?;
 
+        let name_utf8 = convert_cp866_to_utf8(name.to_bytes()).unwrap_or_default();
+
+        game.max_players = conf.capacity.of(&name_utf8);
+        if game.players.len() > game.max_players as usize {
+            warn!(
+                "game_id=`{}` already has `{}` players, more than its capacity `{}`",
+                game.id,
+                game.players.len(),
+                game.max_players
+            );
+        }
+
+        game.lock = match conf.private_games.of(&name_utf8) {
+            GameAccess::Public => None,
+            GameAccess::Preset(pwd) => Some(Lock::Preset(pwd.to_string())),
+            // the creator may be already registered
+            GameAccess::Creator => match game.get_player(client_id).and_then(|p| p.auth.as_ref()) {
+                Some(auth) => match auth.pwd() {
+                    Some(hash) => Some(Lock::Password(hash.clone())),
+                    None => {
+                        warn!("creator of private game_id=`{}` has no password", game.id);
+                        None
+                    }
+                },
+                None => Some(Lock::Creator(client_id)),
+            },
+        };
+        if game.is_locked() {
+            info!("game_id=`{}` is private", game.id);
+        }
+
         Ok(OnUpdateOk::Complete)
     }
 }
@@ -37,6 +37,7 @@ impl OnUpdate_SetPlayerData for Server {
         };
 
         let gmtype = game.get_gmtype();
+        let game_id = game.id;
 
         let player_id = {
             let player = game
@@ -44,7 +45,7 @@ impl OnUpdate_SetPlayerData for Server {
                 .iter_mut()
                 .find(|p| p.client_id == client_id)
                 .unwrap();
-            match (player.set_body(&packet.data), player.bind) {
+            match (player.set_body(gmtype, &packet.data), player.bind) {
                 (Ok(_), Some(bind)) => bind.id(),
                 (Ok(_), None) => return Err(SetPlayerDataError::PlayerNotBind(client_id).into()),
                 (Err(_), _) => return Err(SetPlayerDataError::SliceToBodyParse(gmtype).into()),
@@ -68,6 +69,11 @@ impl OnUpdate_SetPlayerData for Server {
 
         self.notify_game(client_id, &packet);
 
+        // the body has the kills of the player
+        self.check_match(game_id);
+        // and the race time
+        self.submit_race_time(client_id);
+
         Ok(OnUpdateOk::Complete)
     }
 }
@@ -1,6 +1,7 @@
 use crate::Server;
-use crate::protocol::{Action, NetTransportSend, Packet};
-use crate::vanject::{NID, VanjectError};
+use crate::protocol::{Action, NetTransportReceive, NetTransportSend, Packet};
+use crate::server::anticheat::Violation;
+use crate::vanject::{NID, Pos, VanjectError};
 use crate::{client::ClientID, utils::slice_le_to_i32};
 
 use super::{OnUpdateError, OnUpdateOk};
@@ -52,36 +53,126 @@ impl OnUpdate_UpdateObject for Server {
             .map(|bind| bind.id())
             .ok_or(UpdateObjectError::PlayerNotBind(client_id))?;
 
-        let mut packets: Vec<Packet> = vec![];
-
-        match game.vanjects.get_mut(&vanject_id) {
-            Some(vanject) => {
-                vanject
-                    .update_from_slice(&packet.data)
-                    .map_err(UpdateObjectError::SliceToVanjectParse)?;
-
-                vanject.player_bind_id = player_bind_id;
-                if vanject.get_type() == NID::VANGER {
-                    let data = std::iter::empty()
-                        .chain(&[vanject.player_bind_id])
-                        .chain(&vanject.pos.to_vangers_byte())
-                        .copied()
-                        .collect::<Vec<_>>();
-                    packets.push(Packet::new(Action::PLAYERS_POSITION, &data));
-                }
-
-                packets.push(Packet::new(
-                    Action::UPDATE_OBJECT,
-                    &vanject.to_vangers_byte(),
-                ));
+        if !game.vanjects.contains_key(&vanject_id) {
+            Err(UpdateObjectError::VanjectNotFound(vanject_id))?
+        }
+        if let Some(owner) = game.object_owner(vanject_id)
+            && owner != player_bind_id
+        {
+            let violation = Violation::ForeignUpdate {
+                object_id: vanject_id,
+                owner,
+            };
+            self.report_violation(client_id, violation);
+            return Ok(OnUpdateOk::Complete);
+        }
+
+        // positions of vangers are checked before they are applied
+        let is_vanger = game.vanjects[&vanject_id].get_type() == NID::VANGER;
+        if is_vanger && packet.data.len() >= 12 {
+            let time = slice_le_to_i32(&packet.data[4..8]);
+            let pos = Pos::from_slice(&packet.data[8..12]).expect("the slice has 4 bytes");
+            if !self.check_movement(client_id, pos, time) {
+                return Ok(OnUpdateOk::Complete);
             }
-            None => Err(UpdateObjectError::VanjectNotFound(vanject_id))?,
         }
 
-        for p in packets {
-            self.notify_game(client_id, &p);
+        let game = self
+            .get_mut_game_by_clientid(client_id)
+            .expect("we got game by this player in line above");
+        let vanject = game
+            .vanjects
+            .get_mut(&vanject_id)
+            .expect("the vanject is found above");
+        vanject
+            .update_from_slice(&packet.data)
+            .map_err(UpdateObjectError::SliceToVanjectParse)?;
+        vanject.player_bind_id = player_bind_id;
+
+        let update = Packet::new(Action::UPDATE_OBJECT, &vanject.to_vangers_byte());
+        if vanject.get_type() == NID::VANGER {
+            let pos = vanject.pos;
+            let data = std::iter::empty()
+                .chain(&[player_bind_id])
+                .chain(&pos.to_vangers_byte())
+                .copied()
+                .collect::<Vec<_>>();
+
+            // the position of the receiver is needed by `Game::near_receivers`
+            game.get_mut_player(client_id)
+                .expect("we got game by this player in line above")
+                .pos = pos;
+            self.notify_game(client_id, &Packet::new(Action::PLAYERS_POSITION, &data));
         }
+        self.notify_update(client_id, vanject_id, &update);
 
         Ok(OnUpdateOk::Complete)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::fixture;
+    use crate::server::fixture::create_server;
+    use crate::vanject::{NID, Pos};
+
+    #[test]
+    fn foreign_update() {
+        let mut srv = create_server(&["a", "b"], None);
+        let id = fixture::add_vanject(srv.games.get_mut(&1).unwrap(), NID::DEVICE);
+
+        let update = [&id.to_le_bytes()[..], &[7, 0, 0, 0], &[10, 0, 20, 0]].concat();
+        let packet = Packet::new(Action::UPDATE_OBJECT, &update);
+
+        assert!(srv.update_object(&packet, 12).is_ok());
+        assert_eq!(0, srv.games[&1].vanjects[&id].time, "rejected");
+        assert_eq!(1, srv.anticheat.of(12));
+
+        assert!(srv.update_object(&packet, 11).is_ok());
+        assert_eq!(7, srv.games[&1].vanjects[&id].time);
+        assert_eq!(0, srv.anticheat.of(11));
+    }
+
+    #[test]
+    fn teleport() {
+        let mut srv = create_server(&["a"], None);
+        srv.conf.movement.enabled = true;
+        srv.conf.movement.drop_jumps = true;
+        srv.conf.movement.max_speed = 1000;
+        srv.conf.movement.tolerance = 0;
+
+        let game = srv.games.get_mut(&1).unwrap();
+        fixture::place(game, &[(11, Pos { x: 0, y: 0 })]);
+        let id = fixture::add_vanject(game, NID::VANGER);
+
+        let update = |time: i32, x: i16| {
+            let data = [
+                &id.to_le_bytes()[..],
+                &time.to_le_bytes(),
+                &x.to_le_bytes(),
+                &[20, 0, 8],
+            ]
+            .concat();
+            Packet::new(Action::UPDATE_OBJECT, &data)
+        };
+        let pos_x = |srv: &Server| srv.games[&1].vanjects[&id].pos.x;
+
+        assert!(srv.update_object(&update(1000, 10), 11).is_ok());
+        assert!(srv.update_object(&update(1100, 100), 11).is_ok());
+        assert_eq!(100, pos_x(&srv));
+
+        assert!(srv.update_object(&update(1200, 1000), 11).is_ok());
+        assert_eq!(100, pos_x(&srv), "the jump is dropped");
+        assert_eq!(1, srv.anticheat.of(11));
+
+        assert!(srv.update_object(&update(1300, 1050), 11).is_ok());
+        assert_eq!(1050, pos_x(&srv), "the vanger moves on after the jump");
+        assert_eq!(1, srv.anticheat.of(11), "one violation per jump");
+
+        srv.conf.movement.drop_jumps = false;
+        assert!(srv.update_object(&update(1400, 1550), 11).is_ok());
+        assert_eq!(1550, pos_x(&srv), "the jump is reported only");
+        assert_eq!(2, srv.anticheat.of(11));
+    }
+}
@@ -0,0 +1,55 @@
+use ::tracing::error;
+
+use crate::chat_log::ChatEntry;
+use crate::client::ClientID;
+use crate::player::Player;
+use crate::utils::convert_cp866_to_utf8;
+
+use super::Server;
+
+fn player_name(p: &Player) -> String {
+    match p.auth {
+        Some(ref auth) => auth.name_utf8(),
+        None => "[unnamed]".to_string(),
+    }
+}
+
+impl Server {
+    /// Writes the chat message `msg` (CP866 without null-terminator) sent by
+    /// `client_id` to `receivers` into the chat log (if it is enabled).
+    pub(in crate::server) fn log_chat(
+        &mut self,
+        client_id: ClientID,
+        receivers: &[ClientID],
+        msg: &[u8],
+    ) {
+        let Some(ref mut chat_log) = self.chat_log else {
+            return;
+        };
+        let Some(game) = self.games.get_game_by_client_id(client_id) else {
+            return;
+        };
+
+        let mut from = String::new();
+        let mut to = vec![];
+        for p in &game.players {
+            if p.client_id == client_id {
+                from = player_name(p);
+            } else if receivers.contains(&p.client_id) {
+                to.push(player_name(p));
+            }
+        }
+
+        let entry = ChatEntry {
+            game_id: game.id,
+            game_name: &game.name_utf8(),
+            from: &from,
+            to: &to,
+            text: &convert_cp866_to_utf8(msg).unwrap_or_default(),
+        };
+
+        if let Err(err) = chat_log.write(&entry, self.conf.chat_log.keep_days) {
+            error!("{}", err);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use crate::client::ClientID;
+use crate::server::Server;
+
+use super::{ChatCommand, Permission};
+
+/// Kicks a player out of the game: `/kick <name>`.
+pub struct KickCmd;
+
+impl ChatCommand for KickCmd {
+    fn name(&self) -> &'static str {
+        "kick"
+    }
+
+    fn help(&self) -> &'static str {
+        "<name> - kick a player out of the game"
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::Host
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, args: &[&str]) -> Vec<String> {
+        let [name] = args else {
+            return vec![format!("Usage: /{} {}", self.name(), self.help())];
+        };
+
+        let Some(game) = srv.get_game_by_clientid(client_id) else {
+            return vec![];
+        };
+
+        let target = game.players.iter().find(|p| {
+            p.auth
+                .as_ref()
+                .is_some_and(|auth| auth.name_utf8().to_lowercase() == name.to_lowercase())
+        });
+        let Some(target) = target.map(|p| p.client_id) else {
+            return vec![format!("There is no `{}` in the game", name)];
+        };
+
+        if target == client_id || srv.permission_of(target) >= srv.permission_of(client_id) {
+            return vec![format!("You are not allowed to kick `{}`", name)];
+        }
+
+        let others = game
+            .players
+            .iter()
+            .map(|p| p.client_id)
+            .filter(|&id| id != client_id && id != target)
+            .collect::<Vec<_>>();
+
+        srv.kick(target, "You are kicked out of the game by the host");
+        for id in others {
+            srv.notify_system(id, &format!("`{}` is kicked out of the game", name));
+        }
+
+        vec![format!("`{}` is kicked out of the game", name)]
+    }
+}
+
+/// Closes the game for new players: `/close`.
+pub struct CloseCmd;
+
+impl ChatCommand for CloseCmd {
+    fn name(&self) -> &'static str {
+        "close"
+    }
+
+    fn help(&self) -> &'static str {
+        "close the game for new players"
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::Host
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        match srv.get_mut_game_by_clientid(client_id) {
+            Some(game) => {
+                game.closed = true;
+                vec!["The game is closed for new players".to_string()]
+            }
+            None => vec![],
+        }
+    }
+}
+
+/// Opens the game for new players again: `/open`.
+pub struct OpenCmd;
+
+impl ChatCommand for OpenCmd {
+    fn name(&self) -> &'static str {
+        "open"
+    }
+
+    fn help(&self) -> &'static str {
+        "open the game for new players"
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::Host
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        match srv.get_mut_game_by_clientid(client_id) {
+            Some(game) => {
+                game.closed = false;
+                vec!["The game is open for new players".to_string()]
+            }
+            None => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::server::fixture::create_server;
+
+    #[test]
+    fn kick() {
+        let mut srv = create_server(&["host", "griefer", "player"], None);
+
+        assert_eq!(
+            vec!["You are not allowed to kick `host`"],
+            KickCmd.run(&mut srv, 11, &["host"])
+        );
+        assert_eq!(
+            vec!["There is no `nobody` in the game"],
+            KickCmd.run(&mut srv, 11, &["nobody"])
+        );
+        assert_eq!(
+            vec!["`Griefer` is kicked out of the game"],
+            KickCmd.run(&mut srv, 11, &["Griefer"])
+        );
+
+        let game = &srv.games[&1];
+        assert_eq!(
+            vec![11, 13],
+            game.players.iter().map(|p| p.client_id).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn host_passes_to_next_player() {
+        let mut srv = create_server(&["host", "griefer", "player"], None);
+        assert_eq!(Permission::Host, srv.permission_of(11));
+        assert_eq!(Permission::Player, srv.permission_of(12));
+
+        srv.kick(11, "bye");
+        assert_eq!(Some(12), srv.games[&1].host);
+        assert_eq!(Permission::Host, srv.permission_of(12));
+    }
+
+    #[test]
+    fn close() {
+        let mut srv = create_server(&["host", "griefer", "player"], None);
+
+        CloseCmd.run(&mut srv, 11, &[]);
+        assert!(srv.games[&1].closed);
+        OpenCmd.run(&mut srv, 11, &[]);
+        assert!(!srv.games[&1].closed);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::client::ClientID;
+use crate::player::Player;
+use crate::server::Server;
+use crate::utils::DateTime;
+
+use super::{ChatCommand, join_lines};
+
+/// Lists commands available to the player.
+pub struct HelpCmd;
+
+impl ChatCommand for HelpCmd {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn help(&self) -> &'static str {
+        "list of commands"
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        let permission = srv.permission_of(client_id);
+        srv.commands
+            .iter()
+            .filter(|cmd| cmd.permission() <= permission)
+            .map(|cmd| format!("/{} - {}", cmd.name(), cmd.help()))
+            .collect()
+    }
+}
+
+/// Lists players of the game.
+pub struct WhoCmd;
+
+impl ChatCommand for WhoCmd {
+    fn name(&self) -> &'static str {
+        "who"
+    }
+
+    fn help(&self) -> &'static str {
+        "players in the game"
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        let Some(game) = srv.get_game_by_clientid(client_id) else {
+            return vec![];
+        };
+
+        let name = |p: &&Player| match p.auth {
+            Some(ref auth) => auth.name_utf8(),
+            None => "[unnamed]".to_string(),
+        };
+
+        let head = format!("Players ({}/{}): ", game.players.len(), game.max_players);
+        let teams = game.teams();
+        if teams.is_empty() {
+            return join_lines(&head, game.players.iter().map(|p| name(&p)));
+        }
+
+        let mut lines = vec![head];
+        for (team, players) in teams {
+            let head = match team {
+                Some(color) => format!("Team {} ({}): ", color, players.len()),
+                None => format!("No team ({}): ", players.len()),
+            };
+            lines.extend(join_lines(&head, players.iter().map(name)));
+        }
+        lines
+    }
+}
+
+/// Shows server (UTC) and game time.
+pub struct TimeCmd;
+
+impl ChatCommand for TimeCmd {
+    fn name(&self) -> &'static str {
+        "time"
+    }
+
+    fn help(&self) -> &'static str {
+        "server time (UTC) and game time"
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        let now = DateTime::now();
+
+        let mut reply = format!(
+            "Server time: {:02}:{:02}:{:02} UTC",
+            now.hour, now.minute, now.second
+        );
+        if let Some(game) = srv.get_game_by_clientid(client_id) {
+            reply.push_str(&format!(", game time: {}", game.birth_time));
+        }
+
+        vec![reply]
+    }
+}
+
+/// Shows server uptime.
+pub struct UptimeCmd;
+
+impl ChatCommand for UptimeCmd {
+    fn name(&self) -> &'static str {
+        "uptime"
+    }
+
+    fn help(&self) -> &'static str {
+        "server uptime"
+    }
+
+    fn run(&self, srv: &mut Server, _client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        vec![format!("Server uptime: {}", srv.uptime)]
+    }
+}
+
+/// Lists games on the server.
+pub struct GamesCmd;
+
+impl ChatCommand for GamesCmd {
+    fn name(&self) -> &'static str {
+        "games"
+    }
+
+    fn help(&self) -> &'static str {
+        "games on the server"
+    }
+
+    fn run(&self, srv: &mut Server, _client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        let mut games = srv
+            .games
+            .values()
+            .filter(|game| game.is_configured())
+            .collect::<Vec<_>>();
+        games.sort_by_key(|game| game.id);
+
+        let games = games.into_iter().map(|game| {
+            format!(
+                "#{} {} {}/{} {}",
+                game.id,
+                game.name_utf8(),
+                game.players.len(),
+                game.max_players,
+                srv.conf.games_list.types.get(game.get_gmtype())
+            )
+        });
+
+        join_lines("Games: ", games)
+    }
+}
+
+/// Shows rules of the server (`commands.rules`).
+pub struct RulesCmd;
+
+impl ChatCommand for RulesCmd {
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+
+    fn help(&self) -> &'static str {
+        "rules of the server"
+    }
+
+    fn run(&self, srv: &mut Server, _client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        if srv.conf.commands.rules.is_empty() {
+            vec!["There are no special rules on this server".to_string()]
+        } else {
+            srv.conf.commands.rules.clone()
+        }
+    }
+}
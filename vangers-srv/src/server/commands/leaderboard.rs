@@ -0,0 +1,94 @@
+use crate::client::ClientID;
+use crate::leaderboards::{Leaderboards, RaceTime};
+use crate::server::Server;
+
+use super::{ChatCommand, join_lines};
+
+/// Shows the best times of the current race configuration: `/top`.
+pub struct TopCmd;
+
+impl ChatCommand for TopCmd {
+    fn name(&self) -> &'static str {
+        "top"
+    }
+
+    fn help(&self) -> &'static str {
+        "best race times of the game configuration"
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        let Some(game) = srv.get_game_by_clientid(client_id) else {
+            return vec![];
+        };
+        let Some(key) = game.config.as_ref().and_then(Leaderboards::key) else {
+            return vec!["The game has no leaderboard".to_string()];
+        };
+        let name = game
+            .players
+            .iter()
+            .find(|p| p.client_id == client_id)
+            .and_then(|p| p.auth.as_ref())
+            .map(|auth| auth.name_utf8());
+
+        let Some(board) = srv.leaderboards.get(&key) else {
+            return vec![format!("There are no times on `{}` yet", key)];
+        };
+
+        let top = board
+            .ranking()
+            .into_iter()
+            .take(srv.conf.leaderboards.top as usize)
+            .enumerate()
+            .map(|(i, entry)| format!("{}. {} {}", i + 1, entry.name, RaceTime(entry.time)));
+        let mut lines = join_lines(&format!("Top of `{}`: ", key), top);
+
+        if let Some((place, entry)) = name.and_then(|name| board.place_of(&name)) {
+            lines.push(format!(
+                "Your best: {}, place {}",
+                RaceTime(entry.time),
+                place
+            ));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::Type;
+    use crate::server::fixture::create_server;
+
+    #[test]
+    fn top() {
+        let mut srv = create_server(&["Fenex"], Some(Type::PASSEMBLOSS));
+        let key = Leaderboards::key(srv.games[&1].config.as_ref().unwrap()).unwrap();
+        assert_eq!(
+            vec![format!("There are no times on `{}` yet", key)],
+            TopCmd.run(&mut srv, 11, &[])
+        );
+
+        for (name, time) in [("a", 300), ("b", 200), ("fenex", 3725)] {
+            srv.leaderboards.submit(&key, name, time).unwrap();
+        }
+        srv.conf.leaderboards.top = 2;
+
+        assert_eq!(
+            vec![
+                format!("Top of `{}`: 1. b 0:03:20, 2. a 0:05:00", key),
+                "Your best: 1:02:05, place 3".to_string()
+            ],
+            TopCmd.run(&mut srv, 11, &[])
+        );
+    }
+
+    #[test]
+    fn no_leaderboard() {
+        let mut srv = create_server(&["Fenex"], Some(Type::VAN_WAR));
+        assert_eq!(
+            vec!["The game has no leaderboard"],
+            TopCmd.run(&mut srv, 11, &[])
+        );
+    }
+}
@@ -0,0 +1,245 @@
+mod host;
+mod info;
+mod leaderboard;
+mod moderation;
+mod vote;
+
+pub use host::*;
+pub use info::*;
+pub use leaderboard::*;
+pub use moderation::*;
+pub use vote::*;
+
+use std::rc::Rc;
+
+use ::tracing::info;
+
+use crate::client::ClientID;
+use crate::protocol::LIMIT_MSG_LEN;
+use crate::utils::convert_cp866_to_utf8;
+
+use super::Server;
+
+/// Who may run a chat command, levels are ordered from the lowest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Permission {
+    Player,
+    /// Host of the game the player is in, see `Game::host`.
+    Host,
+    /// Players listed in `commands.admins` with a reserved name (see `[accounts]`).
+    Admin,
+}
+
+/// Chat command, run by the server when a player sends `/<name> [args]`.
+pub trait ChatCommand {
+    /// Name of the command without leading `/`.
+    fn name(&self) -> &'static str;
+    /// Short description shown by `/help`.
+    fn help(&self) -> &'static str;
+    fn permission(&self) -> Permission {
+        Permission::Player
+    }
+    /// Runs the command for the player `client_id`.
+    /// Returns lines sent back to that player only.
+    fn run(&self, srv: &mut Server, client_id: ClientID, args: &[&str]) -> Vec<String>;
+}
+
+/// List of all chat commands known by the server.
+pub struct Commands(Vec<Box<dyn ChatCommand>>);
+
+impl Commands {
+    pub fn register(&mut self, cmd: impl ChatCommand + 'static) {
+        self.0.push(Box::new(cmd));
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn ChatCommand> {
+        self.0
+            .iter()
+            .find(|cmd| cmd.name().eq_ignore_ascii_case(name))
+            .map(|cmd| cmd.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn ChatCommand> {
+        self.0.iter().map(|cmd| cmd.as_ref())
+    }
+}
+
+impl Default for Commands {
+    /// Returns all built-in commands.
+    fn default() -> Self {
+        let mut commands = Self(vec![]);
+        commands.register(HelpCmd);
+        commands.register(WhoCmd);
+        commands.register(TimeCmd);
+        commands.register(UptimeCmd);
+        commands.register(GamesCmd);
+        commands.register(RulesCmd);
+        commands.register(TopCmd);
+        commands.register(KickCmd);
+        commands.register(CloseCmd);
+        commands.register(OpenCmd);
+        commands.register(VoteKickCmd);
+        commands.register(MuteCmd);
+        commands.register(UnmuteCmd);
+        commands.register(ViolationsCmd);
+        commands
+    }
+}
+
+/// Joins `items` by `, ` into lines which fit into a chat message,
+/// the first line starts with `head`.
+pub fn join_lines(head: &str, items: impl IntoIterator<Item = String>) -> Vec<String> {
+    // CP866 is single-byte, so the length of the text is the count of chars
+    let limit = LIMIT_MSG_LEN - 1;
+
+    let mut lines = vec![];
+    let mut line = head.to_string();
+    let mut first = true;
+    for item in items {
+        if first {
+            line.push_str(&item);
+        } else if line.chars().count() + 2 + item.chars().count() > limit {
+            lines.push(std::mem::replace(&mut line, item));
+        } else {
+            line.push_str(", ");
+            line.push_str(&item);
+        }
+        first = false;
+    }
+    lines.push(line);
+    lines
+}
+
+impl Server {
+    /// Returns the permission level of the player `client_id`.
+    pub(in crate::server) fn permission_of(&self, client_id: ClientID) -> Permission {
+        let name = self
+            .games
+            .get_player_by_client_id(client_id)
+            .and_then(|(_, p)| p.auth.as_ref())
+            .map(|auth| auth.name_utf8().to_lowercase());
+
+        // the name is proven by the password only if it is reserved
+        let is_admin = match (name, &self.accounts) {
+            (Some(name), Some(accounts)) => {
+                accounts.get(&name).is_some()
+                    && self
+                        .conf
+                        .commands
+                        .admins
+                        .iter()
+                        .any(|admin| admin.to_lowercase() == name)
+            }
+            _ => false,
+        };
+
+        let is_host = self
+            .get_game_by_clientid(client_id)
+            .is_some_and(|game| game.host == Some(client_id));
+
+        if is_admin {
+            Permission::Admin
+        } else if is_host {
+            Permission::Host
+        } else {
+            Permission::Player
+        }
+    }
+
+    /// Runs chat command `msg` (CP866 text starting with `/`) sent by `client_id`.
+    pub(in crate::server) fn run_command(&mut self, client_id: ClientID, msg: &[u8]) {
+        let text = convert_cp866_to_utf8(msg).unwrap_or_default();
+        let mut words = text.trim_start_matches('/').split_whitespace();
+        let name = words.next().unwrap_or_default();
+        let args = words.collect::<Vec<_>>();
+
+        let commands = Rc::clone(&self.commands);
+        let replies = match commands.get(name) {
+            None => vec![format!("Unknown command `/{}`, see /help", name)],
+            Some(cmd) if cmd.permission() > self.permission_of(client_id) => {
+                vec![format!("You are not allowed to use `/{}`", cmd.name())]
+            }
+            Some(cmd) => {
+                info!("client_id=`{}` runs {:?}", client_id, text);
+                cmd.run(self, client_id, &args)
+            }
+        };
+
+        for reply in replies {
+            self.notify_system(client_id, &reply);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::{AccountStore, Checked, Login};
+    use crate::player::PasswordHash;
+    use crate::server::fixture::create_server;
+
+    #[test]
+    fn join_lines_fit_into_message() {
+        assert_eq!(vec!["Players: "], join_lines("Players: ", vec![]));
+        assert_eq!(
+            vec!["Players: a, b"],
+            join_lines("Players: ", ["a", "b"].map(String::from))
+        );
+
+        let lines = join_lines("Names: ", (0..40).map(|i| format!("name-{:02}", i)));
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|l| l.len() < LIMIT_MSG_LEN));
+        assert_eq!(
+            40,
+            lines.iter().map(|l| l.split(", ").count()).sum::<usize>()
+        );
+    }
+
+    #[test]
+    fn admin_permission() {
+        let mut srv = create_server(&["admin", "player"], None);
+        srv.conf.commands.admins = vec!["Admin".to_string()];
+        assert_eq!(Permission::Host, srv.permission_of(11), "no accounts");
+
+        srv.set_accounts(AccountStore::default());
+        let accounts = srv.accounts.as_mut().unwrap();
+        assert_eq!(
+            Login::WrongPassword,
+            accounts.login("admin", Checked::new(None, b"pwd")).unwrap()
+        );
+        assert_eq!(Permission::Host, srv.permission_of(11), "cannot be claimed");
+
+        let accounts = srv.accounts.as_mut().unwrap();
+        accounts.add("admin", PasswordHash::new(b"pwd")).unwrap();
+        assert_eq!(Permission::Admin, srv.permission_of(11));
+        assert_eq!(Permission::Player, srv.permission_of(12));
+    }
+
+    #[test]
+    fn builtin_commands() {
+        let mut srv = create_server(&["admin", "player"], None);
+        let commands = Rc::clone(&srv.commands);
+        let mut run = |name: &str| commands.get(name).unwrap().run(&mut srv, 12, &[]);
+
+        assert_eq!(vec!["Players (2/30): admin, player"], run("who"));
+        assert_eq!(
+            vec!["There are no special rules on this server"],
+            run("RULES")
+        );
+        assert!(run("time")[0].starts_with("Server time: "));
+        assert!(run("uptime")[0].starts_with("Server uptime: "));
+        assert_eq!(
+            vec!["Games: "],
+            run("games"),
+            "unconfigured games are hidden"
+        );
+        assert_eq!(
+            commands
+                .iter()
+                .filter(|cmd| cmd.permission() == Permission::Player)
+                .count(),
+            run("help").len()
+        );
+        assert!(commands.get("unknown").is_none());
+    }
+}
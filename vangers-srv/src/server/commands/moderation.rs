@@ -0,0 +1,110 @@
+use std::time::Duration;
+
+use crate::client::ClientID;
+use crate::server::Server;
+
+use super::{ChatCommand, Permission, join_lines};
+
+/// Mutes a player: `/mute <name> [minutes]`.
+pub struct MuteCmd;
+
+impl ChatCommand for MuteCmd {
+    fn name(&self) -> &'static str {
+        "mute"
+    }
+
+    fn help(&self) -> &'static str {
+        "<name> [minutes] - mute a player"
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::Admin
+    }
+
+    fn run(&self, srv: &mut Server, _client_id: ClientID, args: &[&str]) -> Vec<String> {
+        let (name, minutes) = match args {
+            [name] => (name, None),
+            [name, minutes] => match minutes.parse::<u64>() {
+                Ok(minutes) => (name, Some(minutes)),
+                Err(_) => return vec![format!("Invalid minutes `{}`", minutes)],
+            },
+            _ => return vec![format!("Usage: /{} {}", self.name(), self.help())],
+        };
+
+        srv.moderation
+            .mutes
+            .mute(name, minutes.map(|m| Duration::from_secs(m * 60)));
+
+        match minutes {
+            Some(minutes) => vec![format!("`{}` is muted for {} minutes", name, minutes)],
+            None => vec![format!("`{}` is muted", name)],
+        }
+    }
+}
+
+/// Unmutes a player: `/unmute <name>`.
+pub struct UnmuteCmd;
+
+impl ChatCommand for UnmuteCmd {
+    fn name(&self) -> &'static str {
+        "unmute"
+    }
+
+    fn help(&self) -> &'static str {
+        "<name> - unmute a player"
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::Admin
+    }
+
+    fn run(&self, srv: &mut Server, _client_id: ClientID, args: &[&str]) -> Vec<String> {
+        let [name] = args else {
+            return vec![format!("Usage: /{} {}", self.name(), self.help())];
+        };
+
+        if srv.moderation.mutes.unmute(name) {
+            vec![format!("`{}` is unmuted", name)]
+        } else {
+            vec![format!("`{}` is not muted", name)]
+        }
+    }
+}
+
+/// Lists players with anti-cheat violations: `/violations`.
+pub struct ViolationsCmd;
+
+impl ChatCommand for ViolationsCmd {
+    fn name(&self) -> &'static str {
+        "violations"
+    }
+
+    fn help(&self) -> &'static str {
+        "players with anti-cheat violations"
+    }
+
+    fn permission(&self) -> Permission {
+        Permission::Admin
+    }
+
+    fn run(&self, srv: &mut Server, _client_id: ClientID, _args: &[&str]) -> Vec<String> {
+        let players = srv
+            .games
+            .values()
+            .flat_map(|game| game.players.iter())
+            .filter_map(|p| match srv.anticheat.of(p.client_id) {
+                0 => None,
+                count => Some((p, count)),
+            })
+            .map(|(p, count)| {
+                let name = p.auth.as_ref().map(|auth| auth.name_utf8());
+                format!("{} {}", name.unwrap_or_default(), count)
+            })
+            .collect::<Vec<_>>();
+
+        if players.is_empty() {
+            return vec!["No violations".to_string()];
+        }
+        join_lines("Violations: ", players)
+    }
+}
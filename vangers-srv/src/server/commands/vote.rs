@@ -0,0 +1,214 @@
+use std::time::{Duration, Instant};
+
+use ::tracing::info;
+
+use crate::client::ClientID;
+use crate::game::VoteKick;
+use crate::server::Server;
+
+use super::{ChatCommand, Permission};
+
+/// Starts or supports a vote to kick a player: `/votekick <name>`.
+///
+/// The vote is open while the game has a host too: the host may be away,
+/// and it is passed on only when the host leaves. The host itself cannot
+/// be voted out.
+pub struct VoteKickCmd;
+
+impl ChatCommand for VoteKickCmd {
+    fn name(&self) -> &'static str {
+        "votekick"
+    }
+
+    fn help(&self) -> &'static str {
+        "<name> - vote to kick a player out of the game"
+    }
+
+    fn run(&self, srv: &mut Server, client_id: ClientID, args: &[&str]) -> Vec<String> {
+        let [name] = args else {
+            return vec![format!("Usage: /{} {}", self.name(), self.help())];
+        };
+
+        let conf = srv.conf.vote_kick.clone();
+        let now = Instant::now();
+
+        let Some(game) = srv.get_game_by_clientid(client_id) else {
+            return vec![];
+        };
+
+        if game.players.len() < conf.min_players as usize {
+            return vec![format!(
+                "Vote-kick needs at least {} players in the game",
+                conf.min_players
+            )];
+        }
+
+        let target = game.players.iter().find_map(|p| {
+            let auth_name = p.auth.as_ref()?.name_utf8();
+            (auth_name.to_lowercase() == name.to_lowercase()).then_some((p.client_id, auth_name))
+        });
+        let Some((target, target_name)) = target else {
+            return vec![format!("There is no `{}` in the game", name)];
+        };
+
+        // the host is left to `/kick` griefers, a majority cannot take the game over
+        if target == client_id
+            || game.host == Some(target)
+            || srv.permission_of(target) == Permission::Admin
+        {
+            return vec![format!("You cannot vote to kick `{}`", target_name)];
+        }
+
+        let players = game.players.iter().map(|p| p.client_id).collect::<Vec<_>>();
+        let target_addr = srv.addr_of(target);
+        let game = srv
+            .get_mut_game_by_clientid(client_id)
+            .expect("the game is found above");
+
+        // the previous vote is over if it has timed out or its target has left
+        let mut replies = vec![];
+        if let Some(ref vote) = game.vote_kick
+            && (vote.until <= now || !players.contains(&vote.target))
+        {
+            game.vote_kick = None;
+            replies.push("The previous vote-kick has failed".to_string());
+        }
+
+        let vote = match game.vote_kick {
+            Some(ref mut vote) if vote.target != target => {
+                replies.push("Another vote-kick is running, wait for its end".to_string());
+                return replies;
+            }
+            Some(ref mut vote) if vote.votes.contains(&client_id) => {
+                replies.push("You have already voted".to_string());
+                return replies;
+            }
+            Some(ref mut vote) => {
+                vote.votes.push(client_id);
+                vote
+            }
+            None => game.vote_kick.insert(VoteKick {
+                target,
+                votes: vec![client_id],
+                until: now + Duration::from_secs(conf.window_secs),
+            }),
+        };
+
+        let voters = players.len() - 1;
+        let needed = voters * conf.majority as usize / 100 + 1;
+        let votes = vote.votes.iter().filter(|id| players.contains(id)).count();
+        let left_secs = vote.until.saturating_duration_since(now).as_secs();
+
+        if votes < needed {
+            let text = format!(
+                "Vote-kick `{}`: {}/{}, type /{} {} to agree within {} s",
+                target_name,
+                votes,
+                needed,
+                self.name(),
+                target_name,
+                left_secs
+            );
+            players
+                .iter()
+                .filter(|&&id| id != target)
+                .for_each(|&id| srv.notify_system(id, &text));
+            return replies;
+        }
+
+        info!("client_id=`{}` is kicked by vote", target);
+        game.vote_kick = None;
+        game.ban(
+            &target_name,
+            target_addr,
+            now + Duration::from_secs(conf.ban_minutes * 60),
+        );
+        srv.kick(
+            target,
+            &format!(
+                "You are kicked out of the game by vote for {} minutes",
+                conf.ban_minutes
+            ),
+        );
+
+        let text = format!("`{}` is kicked out of the game by vote", target_name);
+        players
+            .iter()
+            .filter(|&&id| id != target)
+            .for_each(|&id| srv.notify_system(id, &text));
+
+        replies
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::server::fixture::create_server;
+
+    fn client_ids(srv: &Server) -> Vec<ClientID> {
+        srv.games[&1].players.iter().map(|p| p.client_id).collect()
+    }
+
+    #[test]
+    fn vote_kick() {
+        let mut srv = create_server(&["a", "b", "c", "griefer"], None);
+
+        assert_eq!(
+            vec!["You cannot vote to kick `a`"],
+            VoteKickCmd.run(&mut srv, 12, &["a"]),
+            "the host"
+        );
+        assert!(VoteKickCmd.run(&mut srv, 11, &["griefer"]).is_empty());
+        assert_eq!(
+            vec!["You have already voted"],
+            VoteKickCmd.run(&mut srv, 11, &["griefer"])
+        );
+        assert_eq!(
+            vec!["Another vote-kick is running, wait for its end"],
+            VoteKickCmd.run(&mut srv, 12, &["c"])
+        );
+        assert_eq!(vec![11, 12, 13, 14], client_ids(&srv), "1/2 votes");
+
+        assert!(VoteKickCmd.run(&mut srv, 12, &["GRIEFER"]).is_empty());
+        assert_eq!(vec![11, 12, 13], client_ids(&srv), "2/2 votes");
+        assert!(srv.games[&1].vote_kick.is_none());
+        assert!(srv.games[&1].is_banned(Some("Griefer"), None));
+        assert!(!srv.games[&1].is_banned(Some("a"), None));
+    }
+
+    #[test]
+    fn vote_kick_timeout() {
+        let mut srv = create_server(&["a", "b", "c", "griefer"], None);
+
+        VoteKickCmd.run(&mut srv, 11, &["griefer"]);
+        srv.games
+            .get_mut(&1)
+            .unwrap()
+            .vote_kick
+            .as_mut()
+            .unwrap()
+            .until = Instant::now();
+
+        assert_eq!(
+            vec!["The previous vote-kick has failed"],
+            VoteKickCmd.run(&mut srv, 12, &["griefer"])
+        );
+        assert_eq!(
+            vec![12],
+            srv.games[&1].vote_kick.as_ref().unwrap().votes,
+            "the new vote is started"
+        );
+    }
+
+    #[test]
+    fn vote_kick_needs_players() {
+        let mut srv = create_server(&["a", "b"], None);
+
+        assert_eq!(
+            vec!["Vote-kick needs at least 3 players in the game"],
+            VoteKickCmd.run(&mut srv, 11, &["b"])
+        );
+        assert_eq!(vec![11, 12], client_ids(&srv));
+    }
+}
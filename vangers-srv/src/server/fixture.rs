@@ -0,0 +1,15 @@
+//! Servers shared by tests.
+
+use crate::game::{Config, Type, fixture};
+
+use super::Server;
+
+/// Creates a server with the game `1` of players `names` (`client_id` from `11`),
+/// configured as `gametype` if it is given, see `fixture::game`.
+pub(in crate::server) fn create_server(names: &[&str], gametype: Option<Type>) -> Server {
+    let mut srv = Server::new(Default::default());
+    let mut game = fixture::game(names);
+    game.config = gametype.map(Config::new);
+    srv.games.insert(1, game);
+    srv
+}
@@ -0,0 +1,158 @@
+use crate::client::ClientID;
+use crate::protocol::Packet;
+
+use super::Server;
+
+/// The latest `UPDATE_OBJECT` of an object which far receivers have not got,
+/// see `Server::notify_update`.
+#[derive(Debug)]
+pub(in crate::server) struct FarUpdate {
+    packet: Packet,
+    receivers: Vec<ClientID>,
+    /// `false` if the object has been updated since the last tick.
+    quiet: bool,
+}
+
+impl Server {
+    /// Sends `UPDATE_OBJECT` of the object `object_id` like `Server::notify_world`,
+    /// but receivers far from the object get every `interest.far_rate`-th update only.
+    /// The latest update they have missed is sent by `Server::flush_far_updates`.
+    pub(in crate::server) fn notify_update(
+        &mut self,
+        client_id: ClientID,
+        object_id: i32,
+        packet: &Packet,
+    ) {
+        let conf = &self.conf.interest;
+        let Some(game) = self.get_game_by_clientid(client_id) else {
+            return;
+        };
+        let Some(vanject) = game.vanjects.get(&object_id) else {
+            return;
+        };
+
+        if !conf.enabled || !vanject.is_non_global() || conf.is_always(vanject.get_type()) {
+            return self.notify_world(client_id, object_id, packet);
+        }
+
+        let key = (game.id, object_id);
+        if vanject.updates % conf.far_rate == 0 {
+            // far receivers catch up with this update
+            self.far_updates.remove(&key);
+            return self.notify_world(client_id, object_id, packet);
+        }
+
+        let near = game.near_receivers(client_id, object_id, conf.radius);
+        let (near, far) = game
+            .object_receivers(client_id, object_id)
+            .into_iter()
+            .partition::<Vec<_>, _>(|id| near.contains(id));
+
+        self.clients
+            .iter()
+            .filter(|c| near.contains(&c.id))
+            .for_each(|c| c.send(packet));
+
+        self.far_updates.insert(
+            key,
+            FarUpdate {
+                packet: packet.clone(),
+                receivers: far,
+                quiet: false,
+            },
+        );
+    }
+
+    /// Sends far receivers the latest updates of objects which have not been
+    /// updated since the previous call, so they do not miss the final state.
+    pub(in crate::server) fn flush_far_updates(&mut self) {
+        let quiet = self
+            .far_updates
+            .extract_if(|_, update| update.quiet)
+            .collect::<Vec<_>>();
+        self.far_updates
+            .values_mut()
+            .for_each(|update| update.quiet = true);
+
+        for ((game_id, object_id), update) in quiet {
+            // a deleted object must not come back
+            if !self
+                .games
+                .get(&game_id)
+                .is_some_and(|game| game.vanjects.contains_key(&object_id))
+            {
+                continue;
+            }
+
+            self.clients
+                .iter()
+                .filter(|c| update.receivers.contains(&c.id))
+                .for_each(|c| c.send(&update.packet));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::fixture;
+    use crate::protocol::Action;
+    use crate::server::fixture::create_server;
+    use crate::vanject::{NID, Pos};
+
+    fn create_interest_server() -> (Server, i32) {
+        let mut srv = create_server(&["a", "b", "c"], None);
+        srv.conf.interest.enabled = true;
+        srv.conf.interest.radius = 1000;
+        srv.conf.interest.far_rate = 5;
+
+        let game = srv.games.get_mut(&1).unwrap();
+        let pos = |y| Pos { x: 0, y };
+        fixture::place(game, &[(11, pos(0)), (12, pos(100)), (13, pos(5000))]);
+        let id = fixture::add_vanject(game, NID::DEVICE);
+
+        (srv, id)
+    }
+
+    fn update(srv: &mut Server, id: i32, updates: u32) {
+        srv.games
+            .get_mut(&1)
+            .unwrap()
+            .vanjects
+            .get_mut(&id)
+            .unwrap()
+            .updates = updates;
+        srv.notify_update(11, id, &Packet::new(Action::UPDATE_OBJECT, &[]));
+    }
+
+    #[test]
+    fn far_receivers_get_the_latest_update() {
+        let (mut srv, id) = create_interest_server();
+
+        update(&mut srv, id, 1);
+        assert_eq!(vec![13], srv.far_updates[&(1, id)].receivers);
+
+        update(&mut srv, id, 5);
+        assert!(srv.far_updates.is_empty(), "sent to everyone");
+
+        update(&mut srv, id, 6);
+        srv.flush_far_updates();
+        update(&mut srv, id, 7);
+        srv.flush_far_updates();
+        assert_eq!(1, srv.far_updates.len(), "the object is not quiet");
+
+        srv.flush_far_updates();
+        assert!(srv.far_updates.is_empty(), "sent once the object is quiet");
+    }
+
+    #[test]
+    fn deleted_objects_are_not_flushed() {
+        let (mut srv, id) = create_interest_server();
+
+        update(&mut srv, id, 1);
+        srv.games.get_mut(&1).unwrap().vanjects.remove(&id);
+        srv.flush_far_updates();
+        srv.flush_far_updates();
+        assert!(srv.far_updates.is_empty());
+    }
+}
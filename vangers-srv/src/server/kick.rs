@@ -0,0 +1,23 @@
+use ::tracing::info;
+
+use crate::client::ClientID;
+use crate::protocol::{Action, Packet};
+
+use super::Server;
+use super::callback::OnUpdate_CloseSocket;
+
+impl Server {
+    /// Removes the player `client_id` from its game and disconnects the client.
+    /// `reason` is sent to the client as a system message before.
+    pub(in crate::server) fn kick(&mut self, client_id: ClientID, reason: &str) {
+        info!("kick client_id=`{}`: {}", client_id, reason);
+
+        self.notify_system(client_id, reason);
+        self.close_socket(&Packet::new(Action::CLOSE_SOCKET, &[]), client_id)
+            .ok();
+
+        // the socket is not read anymore, it is closed once pending packets are sent
+        self.clients.retain(|c| c.id != client_id);
+        self.anticheat.forget(client_id);
+    }
+}
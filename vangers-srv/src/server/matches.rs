@@ -0,0 +1,180 @@
+use ::tracing::{error, info};
+
+use crate::client::ClientID;
+use crate::game::{Game, GameID, MatchEnd};
+use crate::leaderboards::{Leaderboards, RaceTime};
+use crate::player::Status as PlayerStatus;
+use crate::protocol::{Action, Packet};
+
+use super::Server;
+use super::commands::join_lines;
+
+impl Server {
+    /// Finishes the match of the game `game_id` if its limits are reached.
+    pub(in crate::server) fn check_match(&mut self, game_id: GameID) {
+        let Some(game) = self.games.get_mut_game_by_id(game_id) else {
+            return;
+        };
+        let Some(end) = game.match_end() else {
+            return;
+        };
+
+        let result = game.finish_match(end).clone();
+        info!("game_id=`{}` is over: {:?}", game_id, result);
+
+        let packets = game
+            .players
+            .iter()
+            .filter_map(|p| p.bind)
+            .map(|bind| {
+                Packet::new(
+                    Action::PLAYERS_STATUS,
+                    &[bind.id(), PlayerStatus::FINISHED as u8],
+                )
+            })
+            .collect::<Vec<_>>();
+        let client_ids = game.players.iter().map(|p| p.client_id).collect::<Vec<_>>();
+
+        let reason = match result.end {
+            MatchEnd::Kills => "the kill limit is reached",
+            MatchEnd::Time => "the time is over",
+        };
+        let text = format!(
+            "The match is over in {} min ({}), winner: {}",
+            result.duration.as_secs() / 60,
+            reason,
+            result.winners.join(", ")
+        );
+
+        for client in self.clients.iter().filter(|c| client_ids.contains(&c.id)) {
+            packets.iter().for_each(|packet| client.send(packet));
+        }
+        let scores = join_lines(
+            "Kills/deaths: ",
+            result
+                .scores
+                .iter()
+                .map(|s| format!("{} {}/{}", s.name, s.kills, s.deaths)),
+        );
+        for client_id in client_ids {
+            self.notify_system(client_id, &text);
+            scores
+                .iter()
+                .for_each(|line| self.notify_system(client_id, line));
+        }
+    }
+
+    /// Writes the record of the removed `game` (if records are enabled).
+    pub(in crate::server) fn record_game(&self, game: &Game) {
+        let (Some(results), Some(record)) = (&self.results, game.record()) else {
+            return;
+        };
+
+        match results.write(&record) {
+            Ok(_) => info!("game_id=`{}` is recorded", game.id),
+            Err(err) => error!("{}", err),
+        }
+    }
+
+    /// Records the race time of the player `client_id` to the leaderboard
+    /// of its game if the race is completed. The time is reported by the client,
+    /// so only reserved names get it if accounts are enabled.
+    pub(in crate::server) fn submit_race_time(&mut self, client_id: ClientID) {
+        let Some(game) = self.get_game_by_clientid(client_id) else {
+            return;
+        };
+        let Some(config) = game.config.as_ref() else {
+            return;
+        };
+        let Some(key) = Leaderboards::key(config) else {
+            return;
+        };
+        let Some(player) = game.players.iter().find(|p| p.client_id == client_id) else {
+            return;
+        };
+        let (Some(auth), Some(body)) = (&player.auth, &player.body) else {
+            return;
+        };
+        let Some(time) = Leaderboards::race_time(config, &body.stats) else {
+            return;
+        };
+        let name = auth.name_utf8();
+
+        if let Some(ref accounts) = self.accounts
+            && accounts.get(&name).is_none()
+        {
+            info!(
+                "`{}` is not reserved, its time on `{}` is skipped",
+                name, key
+            );
+            return;
+        }
+
+        match self.leaderboards.submit(&key, &name, time) {
+            Ok(Some(place)) => {
+                info!("`{}` has a new best time {} on `{}`", name, time, key);
+                let text = format!(
+                    "New personal best: {}, place {} on `{}`",
+                    RaceTime(time),
+                    place,
+                    key
+                );
+                self.notify_system(client_id, &text);
+            }
+            Ok(None) => {}
+            Err(err) => error!("{}", err),
+        }
+    }
+
+    /// Finishes matches of all games which limits are reached.
+    pub(in crate::server) fn check_matches(&mut self) {
+        let game_ids = self.games.keys().copied().collect::<Vec<_>>();
+        for game_id in game_ids {
+            self.check_match(game_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::accounts::{AccountStore, Checked};
+    use crate::game::{GameMode, Type};
+    use crate::server::fixture::create_server;
+
+    fn create_race_server(names: &[&str]) -> Server {
+        let mut srv = create_server(names, Some(Type::PASSEMBLOSS));
+        let game = srv.games.get_mut(&1).unwrap();
+        if let Some(GameMode::Passembloss(prm)) =
+            game.config.as_mut().map(|config| &mut config.game_type)
+        {
+            prm.checkpoints_number = 1;
+        }
+
+        // the race is completed in 125 seconds
+        let body = [&[0; 25][..], &125i32.to_le_bytes(), &[1, 0, 0, 0], &[0; 8]].concat();
+        for player in &mut game.players {
+            player.set_body(Type::PASSEMBLOSS, &body).unwrap();
+        }
+        srv
+    }
+
+    fn place_of(srv: &Server, name: &str) -> Option<usize> {
+        let board = srv.leaderboards.get("passembloss/1cp")?;
+        board.place_of(name).map(|(place, _)| place)
+    }
+
+    #[test]
+    fn race_time() {
+        let mut srv = create_race_server(&["Fenex", "Guest"]);
+
+        srv.submit_race_time(11);
+        assert_eq!(Some(1), place_of(&srv, "Fenex"), "accounts are disabled");
+
+        let mut accounts = AccountStore::default();
+        accounts.login("fenex", Checked::new(None, b"pwd")).unwrap();
+        srv.set_accounts(accounts);
+        srv.submit_race_time(12);
+        assert_eq!(None, place_of(&srv, "Guest"), "the name is not reserved");
+    }
+}
@@ -1,5 +1,16 @@
+mod anticheat;
 mod callback;
+mod chat;
+mod commands;
+#[cfg(test)]
+mod fixture;
 mod games;
+mod interest;
+mod kick;
+mod matches;
+mod moderation;
+mod motd;
+mod objects;
 mod server;
 
 pub use server::*;
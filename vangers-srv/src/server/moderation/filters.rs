@@ -0,0 +1,249 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::client::ClientID;
+
+use super::{ChatMessage, Context, Moderator, Verdict};
+
+/// Drops messages of muted players.
+pub struct MuteFilter;
+
+impl Moderator for MuteFilter {
+    fn name(&self) -> &'static str {
+        "mute"
+    }
+
+    fn check(&mut self, msg: &ChatMessage, ctx: &Context) -> Verdict {
+        let name = msg.name.to_lowercase();
+        let muted = ctx.mutes.is_muted(&name, msg.time)
+            || ctx.conf.muted.iter().any(|n| n.to_lowercase() == name);
+
+        if muted {
+            Verdict::Drop("you are muted".to_string())
+        } else {
+            Verdict::Pass
+        }
+    }
+
+    fn report(&self) -> bool {
+        false
+    }
+}
+
+/// Drops a message repeated more than `repeat_limit` times per `repeat_window_secs`.
+#[derive(Default)]
+pub struct AntiSpam {
+    /// Recent messages (lowercase) of each player.
+    history: HashMap<ClientID, VecDeque<(Instant, String)>>,
+}
+
+impl Moderator for AntiSpam {
+    fn name(&self) -> &'static str {
+        "anti-spam"
+    }
+
+    fn check(&mut self, msg: &ChatMessage, ctx: &Context) -> Verdict {
+        if ctx.conf.repeat_limit == 0 {
+            return Verdict::Pass;
+        }
+
+        let window = Duration::from_secs(ctx.conf.repeat_window_secs);
+        self.history.retain(|_, messages| {
+            messages.retain(|(time, _)| msg.time.saturating_duration_since(*time) < window);
+            !messages.is_empty()
+        });
+
+        let text = msg.text.trim().to_lowercase();
+        let messages = self.history.entry(msg.client_id).or_default();
+        let repeats = messages.iter().filter(|(_, t)| *t == text).count();
+        messages.push_back((msg.time, text));
+
+        if repeats >= ctx.conf.repeat_limit as usize {
+            Verdict::Drop("the message is repeated too often".to_string())
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+/// Reports messages containing `watch` words, the case is ignored.
+pub struct WatchList;
+
+impl Moderator for WatchList {
+    fn name(&self) -> &'static str {
+        "watch"
+    }
+
+    fn check(&mut self, msg: &ChatMessage, ctx: &Context) -> Verdict {
+        let text = msg.text.to_lowercase();
+        match ctx
+            .conf
+            .watch
+            .iter()
+            .map(|word| word.trim().to_lowercase())
+            .find(|word| !word.is_empty() && text.contains(word.as_str()))
+        {
+            Some(word) => Verdict::Flag(format!("contains `{}`", word)),
+            None => Verdict::Pass,
+        }
+    }
+}
+
+/// Replaces `words` by `*`, the case is ignored.
+pub struct WordFilter;
+
+impl WordFilter {
+    /// Returns `text` with all `words` masked.
+    fn mask(text: &str, words: &[String]) -> String {
+        let mut chars = text.chars().collect::<Vec<_>>();
+        let lower = chars
+            .iter()
+            .map(|c| c.to_lowercase().next().unwrap_or(*c))
+            .collect::<Vec<_>>();
+
+        for word in words {
+            let word = word
+                .trim()
+                .chars()
+                .map(|c| c.to_lowercase().next().unwrap_or(c))
+                .collect::<Vec<_>>();
+            if word.is_empty() {
+                continue;
+            }
+
+            let mut i = 0;
+            while i + word.len() <= lower.len() {
+                if lower[i..i + word.len()] == word[..] {
+                    chars[i..i + word.len()].fill('*');
+                    i += word.len();
+                } else {
+                    i += 1;
+                }
+            }
+        }
+
+        chars.into_iter().collect()
+    }
+}
+
+impl Moderator for WordFilter {
+    fn name(&self) -> &'static str {
+        "words"
+    }
+
+    fn check(&mut self, msg: &ChatMessage, ctx: &Context) -> Verdict {
+        let masked = Self::mask(msg.text, &ctx.conf.words);
+        if masked != msg.text {
+            Verdict::Rewrite(masked)
+        } else {
+            Verdict::Pass
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Moderation, Outcome};
+    use super::*;
+    use crate::config::ModerationConfig;
+
+    fn message(client_id: ClientID, text: &str, time: Instant) -> ChatMessage<'_> {
+        ChatMessage {
+            client_id,
+            name: if client_id == 1 { "Fenex" } else { "Other" },
+            text,
+            time,
+        }
+    }
+
+    #[test]
+    fn word_filter() {
+        let words = ["bad".to_string(), "Плохо".to_string()];
+        assert_eq!("good", WordFilter::mask("good", &words));
+        assert_eq!(
+            "*** and *** ***",
+            WordFilter::mask("BAD and bad bAd", &words)
+        );
+        assert_eq!("это *****!", WordFilter::mask("это плохо!", &words));
+    }
+
+    #[test]
+    fn pipeline() {
+        let conf = ModerationConfig {
+            words: vec!["bad".to_string()],
+            watch: vec!["cheat".to_string()],
+            repeat_limit: 2,
+            muted: vec!["other".to_string()],
+            ..Default::default()
+        };
+        let mut moderation = Moderation::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            Outcome {
+                text: Some("a *** word".to_string()),
+                reports: vec![],
+                dropped: None
+            },
+            moderation.moderate(&conf, message(1, "a bad word", now))
+        );
+
+        let outcome = moderation.moderate(&conf, message(1, "no CHEATS here", now));
+        assert_eq!(Some("no CHEATS here".to_string()), outcome.text);
+        assert_eq!(vec!["watch flagged: contains `cheat`"], outcome.reports);
+
+        let outcome = moderation.moderate(&conf, message(2, "hello", now));
+        assert_eq!(Some("you are muted".to_string()), outcome.dropped);
+        assert!(outcome.reports.is_empty(), "mutes are not reported");
+
+        assert!(
+            moderation
+                .moderate(&conf, message(1, "spam", now))
+                .text
+                .is_some()
+        );
+        assert!(
+            moderation
+                .moderate(&conf, message(1, "SPAM ", now))
+                .text
+                .is_some()
+        );
+        let outcome = moderation.moderate(&conf, message(1, "spam", now));
+        assert!(outcome.text.is_none());
+        assert_eq!(1, outcome.reports.len());
+
+        // the window is over
+        let later = now + Duration::from_secs(conf.repeat_window_secs);
+        assert!(
+            moderation
+                .moderate(&conf, message(1, "spam", later))
+                .text
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn mutes() {
+        let conf = ModerationConfig::default();
+        let mut moderation = Moderation::default();
+        let now = Instant::now();
+
+        moderation
+            .mutes
+            .mute("FENEX", Some(Duration::from_secs(60)));
+        assert!(
+            moderation
+                .moderate(&conf, message(1, "hi", now))
+                .text
+                .is_none()
+        );
+        assert!(moderation.mutes.unmute("fenex"));
+        assert!(
+            moderation
+                .moderate(&conf, message(1, "hi", now))
+                .text
+                .is_some()
+        );
+        assert!(!moderation.mutes.unmute("fenex"));
+    }
+}
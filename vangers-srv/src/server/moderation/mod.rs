@@ -0,0 +1,213 @@
+mod filters;
+
+pub use filters::*;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use ::tracing::{info, warn};
+
+use crate::client::ClientID;
+use crate::config::ModerationConfig;
+
+use super::Server;
+use super::commands::Permission;
+
+/// Chat message passed through the moderation chain.
+#[derive(Debug)]
+pub struct ChatMessage<'a> {
+    pub client_id: ClientID,
+    /// Name of the sender.
+    pub name: &'a str,
+    /// UTF-8 text, may be rewritten by previous moderators.
+    pub text: &'a str,
+    pub time: Instant,
+}
+
+/// Decision of a moderator about a message.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    /// Pass the message with the given text instead.
+    Rewrite(String),
+    /// Pass the message, but report it with the reason.
+    Flag(String),
+    /// Do not deliver the message, the reason is told to the sender.
+    Drop(String),
+}
+
+/// State shared by all moderators.
+pub struct Context<'a> {
+    pub conf: &'a ModerationConfig,
+    pub mutes: &'a Mutes,
+}
+
+/// One link of the moderation chain.
+pub trait Moderator {
+    fn name(&self) -> &'static str;
+    fn check(&mut self, msg: &ChatMessage, ctx: &Context) -> Verdict;
+    /// Report dropped and flagged messages of this moderator to admins.
+    fn report(&self) -> bool {
+        true
+    }
+}
+
+/// Players muted by admins, by lowercase names.
+#[derive(Debug, Default)]
+pub struct Mutes(HashMap<String, Option<Instant>>);
+
+impl Mutes {
+    /// Mutes `name` for `duration` (forever if `None`).
+    pub fn mute(&mut self, name: &str, duration: Option<Duration>) {
+        let until = duration.map(|d| Instant::now() + d);
+        self.0.insert(name.to_lowercase(), until);
+    }
+
+    /// Returns `false` if `name` was not muted.
+    pub fn unmute(&mut self, name: &str) -> bool {
+        self.0.remove(&name.to_lowercase()).is_some()
+    }
+
+    pub fn is_muted(&self, name: &str, now: Instant) -> bool {
+        match self.0.get(&name.to_lowercase()) {
+            Some(Some(until)) => now < *until,
+            Some(None) => true,
+            None => false,
+        }
+    }
+}
+
+/// Result of `Moderation::moderate`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// Text to deliver, `None` if the message is dropped.
+    pub text: Option<String>,
+    /// Reasons to report to admins.
+    pub reports: Vec<String>,
+    /// Reason of dropping told to the sender.
+    pub dropped: Option<String>,
+}
+
+/// Chain of moderators every chat message goes through.
+pub struct Moderation {
+    chain: Vec<Box<dyn Moderator>>,
+    pub mutes: Mutes,
+}
+
+impl Default for Moderation {
+    /// Returns the chain of built-in moderators.
+    fn default() -> Self {
+        let mut moderation = Self {
+            chain: vec![],
+            mutes: Mutes::default(),
+        };
+        moderation.register(MuteFilter);
+        moderation.register(AntiSpam::default());
+        moderation.register(WatchList);
+        moderation.register(WordFilter);
+        moderation
+    }
+}
+
+impl Moderation {
+    pub fn register(&mut self, moderator: impl Moderator + 'static) {
+        self.chain.push(Box::new(moderator));
+    }
+
+    pub fn moderate(&mut self, conf: &ModerationConfig, msg: ChatMessage) -> Outcome {
+        let ctx = Context {
+            conf,
+            mutes: &self.mutes,
+        };
+
+        let mut text = msg.text.to_string();
+        let mut reports = vec![];
+
+        for moderator in self.chain.iter_mut() {
+            let msg = ChatMessage { text: &text, ..msg };
+
+            match moderator.check(&msg, &ctx) {
+                Verdict::Pass => {}
+                Verdict::Rewrite(rewritten) => {
+                    info!("{}: rewritten message of {:?}", moderator.name(), msg.name);
+                    text = rewritten;
+                }
+                Verdict::Flag(reason) => {
+                    if moderator.report() {
+                        reports.push(format!("{} flagged: {}", moderator.name(), reason));
+                    }
+                }
+                Verdict::Drop(reason) => {
+                    if moderator.report() {
+                        reports.push(format!("{} dropped: {}", moderator.name(), reason));
+                    }
+                    return Outcome {
+                        text: None,
+                        reports,
+                        dropped: Some(reason),
+                    };
+                }
+            }
+        }
+
+        Outcome {
+            text: Some(text),
+            reports,
+            dropped: None,
+        }
+    }
+}
+
+impl Server {
+    /// Passes chat message `text` of `client_id` through the moderation chain.
+    /// Returns the text to deliver or `None` if the message is dropped.
+    pub(in crate::server) fn moderate(
+        &mut self,
+        client_id: ClientID,
+        text: &str,
+    ) -> Option<String> {
+        let name = self
+            .games
+            .get_player_by_client_id(client_id)
+            .and_then(|(_, p)| p.auth.as_ref())
+            .map(|auth| auth.name_utf8())
+            .unwrap_or_default();
+
+        let msg = ChatMessage {
+            client_id,
+            name: &name,
+            text,
+            time: Instant::now(),
+        };
+        let outcome = self.moderation.moderate(&self.conf.moderation, msg);
+
+        for report in &outcome.reports {
+            let report = format!("[mod] {}: {} ({:?})", name, report, text);
+            warn!("{}", report);
+            if self.conf.moderation.report {
+                self.notify_admins(&report);
+            }
+        }
+
+        if let Some(ref reason) = outcome.dropped {
+            self.notify_system(client_id, &format!("Your message is not sent: {}", reason));
+        }
+
+        outcome.text
+    }
+
+    /// Sends `text` as a system message to all admins online.
+    pub(in crate::server) fn notify_admins(&self, text: &str) {
+        let admins = self
+            .games
+            .values()
+            .flat_map(|game| game.players.iter())
+            .map(|p| p.client_id)
+            .filter(|&client_id| self.permission_of(client_id) >= Permission::Admin)
+            .collect::<Vec<_>>();
+
+        for client_id in admins {
+            self.notify_system(client_id, text);
+        }
+    }
+}
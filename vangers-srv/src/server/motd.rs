@@ -0,0 +1,35 @@
+use std::fmt::Display;
+
+use crate::client::ClientID;
+use crate::utils::render;
+
+use super::Server;
+
+impl Server {
+    /// Sends the welcome messages (`[motd]` section of the configuration)
+    /// to the player `client_id`.
+    pub(in crate::server) fn send_motd(&self, client_id: ClientID) {
+        if self.conf.motd.messages.is_empty() {
+            return;
+        }
+
+        let (game_id, players) = self
+            .get_game_by_clientid(client_id)
+            .map(|game| (game.id, game.players.len()))
+            .unwrap_or_default();
+        let online = self.games.values().map(|g| g.players.len()).sum::<usize>();
+
+        let vars: &[(&str, &dyn Display)] = &[
+            ("players", &players),
+            ("online", &online),
+            ("games", &self.games.len()),
+            ("game_id", &game_id),
+            ("uptime", &self.uptime),
+            ("version", &env!("CARGO_PKG_VERSION")),
+        ];
+
+        for msg in &self.conf.motd.messages {
+            self.notify_system(client_id, &render(msg, vars));
+        }
+    }
+}
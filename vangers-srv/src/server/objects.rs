@@ -0,0 +1,147 @@
+use std::time::{Duration, Instant};
+
+use ::tracing::info;
+
+use crate::client::ClientID;
+use crate::game::GameID;
+use crate::protocol::{Action, Packet};
+
+use super::Server;
+
+impl Server {
+    /// Removes objects `object_ids` from the game `game_id`,
+    /// players who see them get `DELETE_OBJECT`.
+    pub(in crate::server) fn delete_objects(&mut self, game_id: GameID, object_ids: &[i32]) {
+        let Some(game) = self.games.get_mut(&game_id) else {
+            return;
+        };
+
+        for id in object_ids {
+            let Some(vanject) = game.vanjects.remove(id) else {
+                continue;
+            };
+
+            let data = std::iter::empty()
+                .chain(&id.to_le_bytes())
+                .chain(&[vanject.player_bind_id])
+                .chain(&vanject.time.to_le_bytes())
+                .copied()
+                .collect::<Vec<_>>();
+            let packet = Packet::new(Action::DELETE_OBJECT, &data);
+
+            let client_ids = game
+                .players
+                .iter()
+                .filter(|p| p.sees_object(*id))
+                .map(|p| p.client_id)
+                .collect::<Vec<_>>();
+            self.clients
+                .iter()
+                .filter(|c| client_ids.contains(&c.id))
+                .for_each(|c| c.send(&packet));
+        }
+    }
+
+    /// Deletes objects of the player `client_id` leaving the game,
+    /// see `Game::owned_objects`.
+    pub(in crate::server) fn delete_player_objects(&mut self, client_id: ClientID) {
+        let Some(game) = self.get_game_by_clientid(client_id) else {
+            return;
+        };
+        let Some(bind) = game.get_player(client_id).and_then(|p| p.bind) else {
+            return;
+        };
+
+        let game_id = game.id;
+        let object_ids = game.owned_objects(bind.id());
+        if !object_ids.is_empty() {
+            info!(
+                "delete {} objects of client_id=`{}`",
+                object_ids.len(),
+                client_id
+            );
+            self.delete_objects(game_id, &object_ids);
+        }
+    }
+
+    /// Deletes objects of all games which `time` has not advanced
+    /// for `objects.stale_secs`, see `Game::stale_objects`.
+    pub(in crate::server) fn sweep_objects(&mut self) {
+        if self.conf.objects.stale_secs == 0 {
+            return;
+        }
+
+        let now = Instant::now();
+        let max_age = Duration::from_secs(self.conf.objects.stale_secs);
+        let stale = self
+            .games
+            .values()
+            .map(|game| (game.id, game.stale_objects(now, max_age)))
+            .filter(|(_, object_ids)| !object_ids.is_empty())
+            .collect::<Vec<_>>();
+
+        for (game_id, object_ids) in stale {
+            info!(
+                "delete {} stale objects of game_id=`{}`",
+                object_ids.len(),
+                game_id
+            );
+            self.delete_objects(game_id, &object_ids);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::game::fixture;
+    use crate::server::callback::OnUpdate_CloseSocket;
+    use crate::server::fixture::create_server;
+    use crate::vanject::NID;
+
+    fn create_objects_server() -> (Server, [i32; 2]) {
+        let mut srv = create_server(&["a", "b"], None);
+
+        // a device of the player, a stuff on the ground
+        let game = srv.games.get_mut(&1).unwrap();
+        let device = fixture::add_vanject(game, NID::DEVICE);
+        let stuff = fixture::add_vanject(game, NID::STUFF);
+
+        (srv, [device, stuff])
+    }
+
+    #[test]
+    fn departing_player() {
+        let (mut srv, [device, stuff]) = create_objects_server();
+
+        assert!(
+            srv.close_socket(&Packet::new(Action::CLOSE_SOCKET, &[]), 11)
+                .is_ok()
+        );
+        let game = &srv.games[&1];
+        assert!(!game.vanjects.contains_key(&device));
+        assert!(game.vanjects.contains_key(&stuff), "outlives the player");
+    }
+
+    #[test]
+    fn sweep() {
+        let (mut srv, [device, _]) = create_objects_server();
+
+        srv.sweep_objects();
+        assert_eq!(2, srv.games[&1].vanjects.len(), "disabled");
+
+        srv.conf.objects.stale_secs = 60;
+        srv.sweep_objects();
+        assert_eq!(2, srv.games[&1].vanjects.len(), "fresh");
+
+        for vanject in srv.games.get_mut(&1).unwrap().vanjects.values_mut() {
+            vanject.advanced -= Duration::from_secs(60);
+        }
+        srv.sweep_objects();
+        assert_eq!(
+            vec![&device],
+            srv.games[&1].vanjects.keys().collect::<Vec<_>>(),
+            "the owner is in the game"
+        );
+    }
+}
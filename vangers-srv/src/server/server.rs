@@ -1,19 +1,41 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::net::IpAddr;
+use std::rc::Rc;
+use std::time::Duration;
+
+use ::clap::Parser;
 use ::tokio::net::TcpListener;
 use ::tokio::sync::mpsc;
-use ::tracing::{error, info};
+use ::tokio::time::MissedTickBehavior;
+use ::tracing::{error, info, warn};
 
+use crate::accounts::AccountStore;
+use crate::chat_log::ChatLog;
 use crate::client::{Client, ClientID, Connection, MpscData};
-use crate::game::Game;
+use crate::config::{Reloader, ServerConfig};
+use crate::game::{Game, GameID};
+use crate::leaderboards::{Leaderboards, RaceTime};
+use crate::player::PasswordHash;
+use crate::protocol::*;
+use crate::results::Results;
 use crate::server::callback::*;
-use crate::utils::Uptime;
-use crate::{ServerConfig, protocol::*};
+use crate::shell::{ShellCmd, SubAccount, SubServer};
+use crate::utils::{Uptime, convert_utf8_to_cp866, shrink_cstr};
 
+use super::anticheat::AntiCheat;
+use super::commands::Commands;
 use super::games::Games;
+use super::interest::FarUpdate;
+use super::moderation::Moderation;
 
 enum Event {
     Add(Client),
     #[allow(dead_code)]
     Halt,
+    /// Re-read the configuration (SIGHUP).
+    Reload,
+    ShellCmd(ShellCmd),
 }
 
 pub struct Server {
@@ -26,7 +48,33 @@ pub struct Server {
     /// List of all connected TCP clients.
     pub(in crate::server) clients: Vec<Client>,
     /// Uptime server
-    uptime: Uptime,
+    pub(in crate::server) uptime: Uptime,
+    /// Re-reads the configuration at runtime, `None` if it is not supported.
+    reloader: Option<Reloader>,
+    /// Read admin commands from stdin.
+    shell: bool,
+    /// Names reserved by passwords, `None` if accounts are disabled.
+    pub(in crate::server) accounts: Option<AccountStore>,
+    /// Log of all chat messages, `None` if it is disabled.
+    pub(in crate::server) chat_log: Option<ChatLog>,
+    /// Records of finished games, `None` if they are disabled.
+    pub(in crate::server) results: Option<Results>,
+    /// Best times of race modes, in memory if no file is configured.
+    pub(in crate::server) leaderboards: Leaderboards,
+    /// Rejected cheat attempts.
+    pub(in crate::server) anticheat: AntiCheat,
+    /// Chain of chat moderators.
+    pub(in crate::server) moderation: Moderation,
+    /// Chat commands, shared to be run with `&mut self`.
+    pub(in crate::server) commands: Rc<Commands>,
+    /// Passes checked passwords of `REGISTER_NAME` back to the event loop,
+    /// `None` before `Server::start`: passwords are checked at once then.
+    pub(in crate::server) password_checks: Option<mpsc::UnboundedSender<CheckedPasswords>>,
+    /// Packets of clients received while their `REGISTER_NAME` passwords are checked,
+    /// see `Server::finish_register`.
+    pub(in crate::server) pending_registers: HashMap<ClientID, Vec<Packet>>,
+    /// Latest object updates which far receivers have not got yet.
+    pub(in crate::server) far_updates: HashMap<(GameID, i32), FarUpdate>,
     // get_game_uniq_id: Box<dyn Fn() -> i32>
 }
 
@@ -38,46 +86,127 @@ impl Server {
             games_id_uniq: 0,
             clients: vec![],
             uptime: Uptime::new(),
-            // shell: None,
+            reloader: None,
+            shell: false,
+            accounts: None,
+            chat_log: None,
+            results: None,
+            leaderboards: Leaderboards::default(),
+            anticheat: AntiCheat::default(),
+            moderation: Moderation::default(),
+            commands: Rc::new(Commands::default()),
+            password_checks: None,
+            pending_registers: HashMap::new(),
+            far_updates: HashMap::new(),
             // get_game_uniq_id: Box::new(q),
         }
     }
 
-    // pub fn enable_shell(&mut self) {
-    //     self.shell = Some(::tokio::spawn(async move {
-    //         println!("Interactive shell enabled");
-    //         let stdin = io::stdin();
-    //         let stdout = io::stdout();
-
-    //         loop {
-    //             {
-    //                 let mut stdout = stdout.lock();
-    //                 stdout.write_all("vangers-srv shell> ".as_bytes()).unwrap();
-    //                 stdout.flush().unwrap();
-    //             }
-
-    //             let mut input = String::new();
-    //             match stdin.read_line(&mut input) {
-    //                 Ok(_) => match input.trim() {
-    //                     "" => continue,
-    //                     "quit" | "exit" => {
-    //                         println!("Interactive shell will be terminated");
-    //                         return;
-    //                     }
-    //                     cmd => {
-    //                         let cmd = std::iter::once("").chain(cmd.split_whitespace());
-
-    //                         match ShellCmd::try_parse_from(cmd) {
-    //                             Ok(shell) => println!("OK command: {:?}", shell),
-    //                             Err(err) => println!("Error command: {}", err),
-    //                         }
-    //                     }
-    //                 },
-    //                 Err(error) => println!("error: {}", error),
-    //             }
-    //         }
-    //     }));
-    // }
+    pub fn set_reloader(&mut self, reloader: Reloader) {
+        self.reloader = Some(reloader);
+    }
+
+    pub fn set_accounts(&mut self, mut accounts: AccountStore) {
+        accounts.reserve(&self.conf.commands.admins);
+        self.accounts = Some(accounts);
+    }
+
+    pub fn set_chat_log(&mut self, chat_log: ChatLog) {
+        self.chat_log = Some(chat_log);
+    }
+
+    pub fn set_results(&mut self, results: Results) {
+        self.results = Some(results);
+    }
+
+    pub fn set_leaderboards(&mut self, leaderboards: Leaderboards) {
+        self.leaderboards = leaderboards;
+    }
+
+    /// Enables interactive shell: admin commands will be read from stdin.
+    pub fn enable_shell(&mut self) {
+        self.shell = true;
+    }
+
+    /// Re-reads the configuration and applies it to the running server
+    /// without disconnecting anyone.
+    pub fn reload_config(&mut self) {
+        let Some(ref reloader) = self.reloader else {
+            warn!("config reload is not supported");
+            return;
+        };
+
+        match reloader.reload(&self.conf) {
+            Ok(conf) => {
+                if let Some(ref mut accounts) = self.accounts {
+                    accounts.reserve(&conf.commands.admins);
+                }
+                self.conf = conf;
+            }
+            Err(err) => error!(
+                "config reload failed, current configuration is kept: {}",
+                err
+            ),
+        }
+    }
+
+    /// Executes admin command. Returns `true` if the server should be halted.
+    fn do_shell(&mut self, cmd: ShellCmd) -> bool {
+        match cmd {
+            ShellCmd::Reload => self.reload_config(),
+            ShellCmd::Server(server) => match server.subcmd {
+                SubServer::Status => println!(
+                    "clients: {}, games: {}, players: {}, accounts: {}, leaderboards: {}, violations: {}",
+                    self.clients.len(),
+                    self.games.len(),
+                    self.games.values().map(|g| g.players.len()).sum::<usize>(),
+                    self.accounts.as_ref().map_or(0, |a| a.len()),
+                    self.leaderboards.len(),
+                    self.anticheat.total()
+                ),
+                SubServer::Uptime => println!("uptime: {}", self.uptime),
+                SubServer::Shutdown => return true,
+            },
+            ShellCmd::Game => self.games.values().for_each(|game| println!("{:#?}", game)),
+            ShellCmd::Player => self
+                .games
+                .values()
+                .flat_map(|game| game.players.iter().map(move |p| (game.id, p)))
+                .for_each(|(game_id, p)| {
+                    println!(
+                        "game_id={}: {:?}, violations: {}",
+                        game_id,
+                        p,
+                        self.anticheat.of(p.client_id)
+                    )
+                }),
+            ShellCmd::Leaderboards => {
+                for (key, board) in self.leaderboards.iter() {
+                    println!("{}:", key);
+                    for (i, entry) in board.ranking().iter().enumerate() {
+                        println!("  {}. {} {}", i + 1, entry.name, RaceTime(entry.time));
+                    }
+                }
+            }
+            ShellCmd::Account(account) => match account.subcmd {
+                SubAccount::Add { name, password } => {
+                    let Some(ref mut accounts) = self.accounts else {
+                        println!("accounts are disabled");
+                        return false;
+                    };
+                    let pwd = convert_utf8_to_cp866(&password);
+                    match accounts.add(&name, PasswordHash::new(&pwd[..pwd.len() - 1])) {
+                        Ok(true) => println!("name `{}` is reserved", name),
+                        Ok(false) => println!("name `{}` is already reserved", name),
+                        Err(err) => println!("{}", err),
+                    }
+                }
+            },
+            ShellCmd::Exit => {}
+        }
+
+        false
+    }
 
     /// Returns the time since server was started in milliseconds.
     pub fn uptime(&self) -> u32 {
@@ -105,6 +234,14 @@ impl Server {
         self.games.get_mut_game_by_client_id(client_id)
     }
 
+    /// Returns the address the client `client_id` has connected from.
+    pub(in crate::server) fn addr_of(&self, client_id: ClientID) -> Option<IpAddr> {
+        self.clients
+            .iter()
+            .find(|c| c.id == client_id)
+            .and_then(|c| c.addr)
+    }
+
     pub(in crate::server) fn get_game_uniq_id(&mut self) -> u32 {
         self.games_id_uniq += 1;
         self.games_id_uniq
@@ -150,6 +287,44 @@ impl Server {
         self.notify(client_id, packet, Box::new(move |&id| id != client_id));
     }
 
+    /// Sends `packet` about the object `object_id` to other clients in the game
+    /// which need it: clients in the world of the object, or all for global objects.
+    pub fn notify_world(&self, client_id: ClientID, object_id: i32, packet: &Packet) {
+        let client_ids = match self.get_game_by_clientid(client_id) {
+            Some(game) => game.object_receivers(client_id, object_id),
+            None => {
+                error!(
+                    "cannot doing notify: player with client_id=`{}` not found on the server",
+                    client_id
+                );
+                return;
+            }
+        };
+
+        self.clients
+            .iter()
+            .filter(|c| client_ids.contains(&c.id))
+            .for_each(|c| c.send(packet));
+    }
+
+    /// Sends `text` to the current client only as a system message
+    /// (`DIRECT_RECEIVING` from `SYSTEM_SENDER_ID`).
+    pub fn notify_system(&self, client_id: ClientID, text: &str) {
+        let msg = convert_utf8_to_cp866(text);
+        let data = std::iter::once(SYSTEM_SENDER_ID)
+            .chain(shrink_cstr(&msg, LIMIT_MSG_LEN).iter().copied())
+            .collect::<Vec<_>>();
+
+        let packet = Packet::new(Action::DIRECT_RECEIVING, &data);
+        match self.clients.iter().find(|c| c.id == client_id) {
+            Some(client) => client.send(&packet),
+            None => warn!(
+                "cannot send system message: client_id=`{}` not found",
+                client_id
+            ),
+        }
+    }
+
     /// Sends `packet` to all clients.
     pub fn notify_all(&self, client_id: ClientID, packet: &Packet) {
         self.notify(client_id, packet, Box::new(|_| true));
@@ -158,11 +333,55 @@ impl Server {
     pub async fn start(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let (client_tx, mut clients_rx) = mpsc::channel(50);
         let (event_tx, mut event_rx) = mpsc::channel::<Event>(10);
+        let (checks_tx, mut checks_rx) = mpsc::unbounded_channel();
+        self.password_checks = Some(checks_tx);
 
         let endpoint = format!("0.0.0.0:{}", self.conf.port);
         println!("Server is listening on: {}", endpoint);
         let listener = TcpListener::bind(endpoint).await?;
 
+        #[cfg(unix)]
+        {
+            use ::tokio::signal::unix::{SignalKind, signal};
+
+            let event_tx = event_tx.clone();
+            let mut sighup = signal(SignalKind::hangup())?;
+            ::tokio::spawn(async move {
+                while sighup.recv().await.is_some() {
+                    info!("SIGHUP received");
+                    if event_tx.send(Event::Reload).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+
+        if self.shell {
+            let event_tx = event_tx.clone();
+            // stdin is blocking, so it is read in the separate thread
+            std::thread::spawn(move || {
+                println!("Interactive shell enabled, type `help` to get list of commands");
+                for line in std::io::stdin().lock().lines() {
+                    let Ok(line) = line else { break };
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+
+                    let cmd = std::iter::once("").chain(line.split_whitespace());
+                    match ShellCmd::try_parse_from(cmd) {
+                        Ok(ShellCmd::Exit) => break,
+                        Ok(cmd) => {
+                            if event_tx.blocking_send(Event::ShellCmd(cmd)).is_err() {
+                                break;
+                            }
+                        }
+                        Err(err) => println!("{}", err),
+                    }
+                }
+                println!("Interactive shell will be terminated");
+            });
+        }
+
         ::tokio::spawn(async move {
             // listening for connecting new clients
             loop {
@@ -177,17 +396,45 @@ impl Server {
             }
         });
 
+        // match clocks, stale objects, missed far updates and login times
+        // of accounts are checked every second
+        let mut tick = ::tokio::time::interval(Duration::from_secs(1));
+
+        // queued packets of all clients are sent every send tick
+        let batching = self.conf.batching.tick_ms > 0;
+        let mut send_tick =
+            ::tokio::time::interval(Duration::from_millis(self.conf.batching.tick_ms.max(1)));
+        send_tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
         loop {
             ::tokio::select! {
+                _ = tick.tick() => {
+                    self.check_matches();
+                    self.sweep_objects();
+                    self.flush_far_updates();
+                    if let Some(Err(err)) = self.accounts.as_mut().map(AccountStore::flush) {
+                        error!("{}", err);
+                    }
+                }
+                _ = send_tick.tick(), if batching => self.clients.iter().for_each(Client::flush),
+                Some(checked) = checks_rx.recv() => self.finish_register(checked),
                 event = event_rx.recv() => {
                     match event {
-                        Some(Event::Add(client)) => {
+                        Some(Event::Add(mut client)) => {
+                            if batching {
+                                client.enable_batching();
+                            }
                             self.clients.push(client);
                         }
                         Some(Event::Halt) => {
                             return Ok(());
                         }
-                        // Ok(Event::ShellCmd(cmd)) => self.do_shell(cmd),
+                        Some(Event::Reload) => self.reload_config(),
+                        Some(Event::ShellCmd(cmd)) => {
+                            if self.do_shell(cmd) {
+                                return Ok(());
+                            }
+                        }
                         None => {
                             error!("unexpected event_rx channel closed");
                             return Ok(());
@@ -203,6 +450,7 @@ impl Server {
                             //     game.players.retain(|p| p.client_id != id)
                             // }
                             self.clients.retain(|c| c.id != id);
+                            self.anticheat.forget(id);
                             // if let Some(client) = self.clients.iter_mut().find(|c| c.id == id) {
                             //     client.connection = Connection::Disconnected;
                             // }
@@ -1,28 +1,43 @@
 use clap::Parser;
 
 #[derive(Parser, Debug)]
+#[clap(name = "shell")]
 pub enum ShellCmd {
     // #[clap(subcommand)]
     Server(Server),
     Game,
     Player,
-    Help,
+    /// Print best times of all leaderboards
+    Leaderboards,
+    Account(Account),
+    /// Re-read the configuration file and apply what can be changed at runtime
+    Reload,
     Exit,
 }
 
 #[derive(Parser, Debug)]
-enum SubServer {
+pub enum SubServer {
     Status,
     Shutdown,
     Uptime,
 }
 
+#[derive(Parser, Debug)]
+pub enum SubAccount {
+    /// Reserve a name by a password, e.g. of an admin
+    Add { name: String, password: String },
+}
+
+#[derive(Parser, Debug)]
+pub struct Account {
+    #[clap(subcommand)]
+    pub subcmd: SubAccount,
+}
+
 #[derive(Parser, Debug)]
 pub struct Server {
     #[clap(subcommand)]
-    subcmd: SubServer,
-    #[clap(long, short)]
-    test: bool,
+    pub subcmd: SubServer,
 }
 
 // #[derive(Parser, Debug)]
@@ -0,0 +1,85 @@
+use std::fmt::Display;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Returns current unix time in seconds.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// UTC date and time, displayed as `2024-02-29T13:05:09Z`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+}
+
+impl DateTime {
+    pub fn now() -> Self {
+        Self::from_unix(unix_now())
+    }
+
+    pub fn from_unix(secs: u64) -> Self {
+        let days = (secs / 86400) as i64;
+        let time = (secs % 86400) as u32;
+
+        // "civil from days" by Howard Hinnant
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+        Self {
+            year,
+            month,
+            day,
+            hour: time / 3600,
+            minute: time / 60 % 60,
+            second: time % 60,
+        }
+    }
+
+    /// Returns date as `2024-02-29`.
+    pub fn date(&self) -> String {
+        format!("{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}T{:02}:{:02}:{:02}Z",
+            self.date(),
+            self.hour,
+            self.minute,
+            self.second
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn from_unix() {
+        assert_eq!("1970-01-01T00:00:00Z", DateTime::from_unix(0).to_string());
+        assert_eq!(
+            "2024-02-29T13:05:09Z",
+            DateTime::from_unix(1709211909).to_string()
+        );
+        assert_eq!("2000-03-01", DateTime::from_unix(951868800).date());
+    }
+}
@@ -1,5 +1,11 @@
+mod datetime;
+mod template;
+mod toml_file;
 mod uptime;
 mod util;
 
+pub use datetime::*;
+pub use template::*;
+pub use toml_file::*;
 pub use uptime::Uptime;
 pub use util::*;
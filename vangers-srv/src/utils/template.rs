@@ -0,0 +1,82 @@
+use std::fmt::Display;
+
+/// Replaces each `{key}` placeholder in `template` by its value from `vars`.
+/// Unknown placeholders are kept as is.
+pub fn render(template: &str, vars: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let value = rest
+            .find('}')
+            .and_then(|end| vars.iter().find(|(k, _)| *k == &rest[1..end]))
+            .map(|(k, v)| (k.len() + 2, v));
+
+        match value {
+            Some((len, value)) => {
+                out.push_str(&value.to_string());
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/// Returns all `{key}` placeholders of `template` that are missed in `known`.
+pub fn unknown_placeholders<'a>(template: &'a str, known: &[&str]) -> Vec<&'a str> {
+    let mut unknown = vec![];
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        if let Some(end) = rest
+            .find(['{', '}'])
+            .filter(|&end| rest[end..].starts_with('}'))
+        {
+            if !known.contains(&&rest[..end]) {
+                unknown.push(&rest[..end]);
+            }
+            rest = &rest[end + 1..];
+        }
+    }
+
+    unknown
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let vars: &[(&str, &dyn Display)] = &[("players", &3), ("uptime", &"1:02:03")];
+
+        assert_eq!("", render("", vars));
+        assert_eq!("no placeholders", render("no placeholders", vars));
+        assert_eq!("3 players", render("{players} players", vars));
+        assert_eq!("3/3 1:02:03", render("{players}/{players} {uptime}", vars));
+        assert_eq!("{unknown} 3", render("{unknown} {players}", vars));
+        assert_eq!("{{3}} {", render("{{{players}}} {", vars));
+    }
+
+    #[test]
+    fn test_unknown_placeholders() {
+        let known = &["players", "uptime"];
+
+        assert!(unknown_placeholders("{players} {uptime} }{", known).is_empty());
+        assert_eq!(
+            vec!["name"],
+            unknown_placeholders("{name}: {players}", known)
+        );
+        assert_eq!(vec!["", "b"], unknown_placeholders("{}{a{b}", known));
+    }
+}
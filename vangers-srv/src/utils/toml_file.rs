@@ -0,0 +1,68 @@
+use std::path::{Path, PathBuf};
+
+use ::serde::Serialize;
+use ::serde::de::DeserializeOwned;
+
+#[derive(Debug, ::thiserror::Error)]
+pub enum TomlFileError {
+    #[error("cannot read file `{0}`: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("cannot parse file `{0}`: {1}")]
+    Parse(PathBuf, ::toml::de::Error),
+    #[error("cannot serialize file `{0}`: {1}")]
+    Serialize(PathBuf, ::toml::ser::Error),
+    #[error("cannot write file `{0}`: {1}")]
+    Write(PathBuf, std::io::Error),
+}
+
+/// Reads the TOML file at `path`, the default value if there is no file yet.
+pub fn load_toml<T: DeserializeOwned + Default>(path: &Path) -> Result<T, TomlFileError> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => {
+            ::toml::from_str(&content).map_err(|err| TomlFileError::Parse(path.to_owned(), err))
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(err) => Err(TomlFileError::Read(path.to_owned(), err)),
+    }
+}
+
+/// Writes `value` to the TOML file at `path`. A temporary file is written
+/// first, so the previous content is not lost on a crash.
+pub fn save_toml<T: Serialize>(path: &Path, value: &T) -> Result<(), TomlFileError> {
+    let content = ::toml::to_string_pretty(value)
+        .map_err(|err| TomlFileError::Serialize(path.to_owned(), err))?;
+
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, content)
+        .and_then(|_| std::fs::rename(&tmp, path))
+        .map_err(|err| TomlFileError::Write(path.to_owned(), err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn save_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("vangers-srv-toml-file-{}.toml", std::process::id()));
+        std::fs::remove_file(&path).ok();
+
+        let empty: BTreeMap<String, u32> = load_toml(&path).unwrap();
+        assert!(empty.is_empty(), "no file yet");
+
+        let value = BTreeMap::from([("a".to_string(), 1u32)]);
+        save_toml(&path, &value).unwrap();
+        assert_eq!(value, load_toml(&path).unwrap());
+        assert!(!path.with_extension("tmp").exists());
+
+        std::fs::write(&path, "a = ").unwrap();
+        assert!(matches!(
+            load_toml::<BTreeMap<String, u32>>(&path),
+            Err(TomlFileError::Parse(..))
+        ));
+
+        std::fs::remove_file(&path).ok();
+    }
+}
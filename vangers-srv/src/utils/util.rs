@@ -1,7 +1,8 @@
+use std::borrow::Cow;
 use std::convert::TryInto;
 
 use ::encoding::all::IBM866;
-use ::encoding::{DecoderTrap, Encoding};
+use ::encoding::{DecoderTrap, EncoderTrap, Encoding};
 
 /// Returns a valid CStr with null-terminate byte from `bytes` slice.
 /// Returns `None` if the first byte is `0x00`
@@ -23,6 +24,31 @@ pub fn convert_cp866_to_utf8(cstr: &[u8]) -> Option<String> {
     IBM866.decode(cstr, DecoderTrap::Replace).ok()
 }
 
+/// Returns `s` encoded in CP866 with null-terminator.
+/// Symbols that cannot be encoded are replaced by `?`.
+pub fn convert_utf8_to_cp866(s: &str) -> Vec<u8> {
+    let mut cstr = IBM866
+        .encode(s, EncoderTrap::Replace)
+        .unwrap_or_else(|_| s.bytes().filter(u8::is_ascii).collect());
+    cstr.retain(|&b| b != 0);
+    cstr.push(0);
+    cstr
+}
+
+/// Cuts null-terminated `cstr` to `limit` bytes (including null-terminator),
+/// the tail of the cut string is replaced by `...`.
+pub fn shrink_cstr(cstr: &[u8], limit: usize) -> Cow<'_, [u8]> {
+    if cstr.len() <= limit {
+        return Cow::Borrowed(cstr);
+    }
+
+    let mut buffer = Vec::with_capacity(limit);
+    buffer.extend_from_slice(&cstr[0..limit - 3 - 1]);
+    buffer.extend_from_slice(b"...");
+    buffer.push(0);
+    Cow::Owned(buffer)
+}
+
 #[allow(dead_code)]
 #[inline(always)]
 pub fn slice_le_to_u16(s: &[u8]) -> u16 {
@@ -156,6 +182,22 @@ mod test {
         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF, 0x00,
     ];
 
+    #[test]
+    fn test_convert_utf8_to_cp866() {
+        assert_eq!(b"test\0", &convert_utf8_to_cp866("test")[..]);
+        assert_eq!(&data[5..20], &convert_utf8_to_cp866("ВВЕДИТЕ СТРОКУ")[..]);
+        assert_eq!(b"a?b\0", &convert_utf8_to_cp866("a\u{1F600}b")[..]);
+        assert_eq!(b"ab\0", &convert_utf8_to_cp866("a\0b")[..]);
+    }
+
+    #[test]
+    fn test_shrink_cstr() {
+        assert_eq!(&b"test\0"[..], &shrink_cstr(b"test\0", 5)[..]);
+        assert_eq!(&b"test\0"[..], &shrink_cstr(b"test\0", 140)[..]);
+        assert_eq!(&b"te...\0"[..], &shrink_cstr(b"test-test\0", 6)[..]);
+        assert_eq!(140, shrink_cstr(&[b'a'; 200], 140).len());
+    }
+
     #[test]
     fn test_get_first_str() {
         assert_eq!(get_first_cstr(&data[0..]), Some(&data[0..5]));
@@ -4,6 +4,7 @@ use crate::player::Player;
 use crate::protocol::{NetTransportReceive, NetTransportSend};
 use crate::utils::{slice_le_to_i16, slice_le_to_i32};
 use std::fmt;
+use std::time::Instant;
 
 #[allow(dead_code)]
 #[allow(non_snake_case)]
@@ -21,6 +22,27 @@ pub mod NID {
     pub const SENSOR: i32 = (12 << 16) | (1 << 31);
     pub const TNT: i32 = (14 << 16) | (1 << 31);
     pub const TERRAIN: i32 = (15 << 16) | (1 << 31);
+
+    /// Names of object types used by the configuration.
+    pub const NAMES: [(&str, i32); 9] = [
+        ("global", GLOBAL),
+        ("device", DEVICE),
+        ("slot", SLOT),
+        ("shell", SHELL),
+        ("vanger", VANGER),
+        ("stuff", STUFF),
+        ("sensor", SENSOR),
+        ("tnt", TNT),
+        ("terrain", TERRAIN),
+    ];
+
+    /// Returns the type (see `get_vanject_type`) named `name`.
+    pub fn by_name(name: &str) -> Option<i32> {
+        NAMES
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|&(_, nid)| super::get_vanject_type(nid))
+    }
 }
 
 #[allow(dead_code)]
@@ -29,13 +51,11 @@ fn client_id(id: i32) -> i32 {
     (id >> 26) & 31
 }
 
-#[allow(dead_code)]
 #[inline(always)]
 pub fn get_station(id: i32) -> i32 {
     (id >> 26) & 31
 }
 
-#[allow(dead_code)]
 #[inline(always)]
 pub fn get_world(id: i32) -> i32 {
     (id >> 22) & 15
@@ -52,13 +72,11 @@ pub fn is_non_static(id: i32) -> bool {
     (id as u32 & (1 << 31)) == 0u32
 }
 
-#[allow(dead_code)]
 #[inline(always)]
 pub fn is_players_vanject(id: i32) -> bool {
     (id as u32 & (7 << 16 + 3)) == 0u32
 }
 
-#[allow(dead_code)]
 #[inline(always)]
 pub fn is_private_vanject(id: i32) -> bool {
     ((id >> 16) & 63) >= 8 && ((id >> 16) & 63) <= 10
@@ -69,6 +87,13 @@ pub fn is_non_global_vanject(id: i32) -> bool {
     ((id as u32 >> 16) & 63) != 0u32
 }
 
+/// Returns `true` if objects with `id` belong to the player of their station:
+/// private objects (mechos) and non-global objects of players (inventory, shells).
+#[inline(always)]
+pub fn is_owned_vanject(id: i32) -> bool {
+    is_private_vanject(id) || (is_players_vanject(id) && is_non_global_vanject(id))
+}
+
 #[allow(dead_code, non_camel_case_types)]
 pub enum PlayerStatus {
     INITIAL_STATUS = 0,
@@ -183,9 +208,12 @@ pub struct Vanject {
     pub player_bind_id: u8,
     pub time: i32,
     pub pos: Pos<i16>,
-    #[allow(dead_code)]
     pub radius: i16,
     pub body: Vec<u8>,
+    /// Count of successful `update_from_slice` calls.
+    pub updates: u32,
+    /// When the object was created or its `time` advanced last.
+    pub advanced: Instant,
 }
 
 impl Vanject {
@@ -216,6 +244,8 @@ impl Vanject {
             pos,
             radius,
             body: body.to_vec(),
+            updates: 0,
+            advanced: Instant::now(),
         })
     }
 
@@ -242,9 +272,13 @@ impl Vanject {
             &slice[12..]
         };
 
+        if time.wrapping_sub(self.time) > 0 {
+            self.advanced = Instant::now();
+        }
         self.time = time;
         self.pos = pos;
         self.body = body.to_vec();
+        self.updates = self.updates.wrapping_add(1);
 
         Ok(())
     }
@@ -294,7 +328,6 @@ impl Vanject {
         is_private_vanject(self.id)
     }
 
-    #[allow(dead_code)]
     #[inline(always)]
     pub fn is_non_global(&self) -> bool {
         is_non_global_vanject(self.id)
@@ -404,6 +437,13 @@ mod test {
         assert_eq!(true, is_non_global_vanject(a));
     }
 
+    #[test]
+    fn nid_by_name() {
+        assert_eq!(Some(NID::VANGER), NID::by_name("Vanger"));
+        assert_eq!(Some(get_vanject_type(NID::SENSOR)), NID::by_name("sensor"));
+        assert_eq!(None, NID::by_name("mechos"));
+    }
+
     mod create_from_slice {
         use super::*;
 
@@ -578,6 +618,7 @@ mod test {
             assert_eq!(15, v.radius);
             assert_eq!(&[88], &v.body[..]);
             assert_eq!(9, v.player_bind_id);
+            assert_eq!(1, v.updates);
         }
 
         #[test]